@@ -0,0 +1,103 @@
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// static files compiled into the binary, by the name they are linked with and the file they are built from
+const ASSETS: [(&str, &str); 5] = [
+    ("css/output.css", "css/output.css"),
+    ("js/errors.js", "js/errors.js"),
+    ("js/timeline.js", "js/timeline.js"),
+    ("js/htmx.min.js", "node_modules/htmx.org/dist/htmx.min.js"),
+    ("js/sse.js", "node_modules/htmx.org/dist/ext/sse.js"),
+];
+
+const FONTAWESOME: &str = "node_modules/@fortawesome/fontawesome-free";
+
+// a file to embed, with its content hash in the served name so it can be cached forever
+struct Asset {
+    name: String,
+    path: String,
+    etag: String,
+    file: PathBuf,
+}
+
+fn main() {
+    let root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let mut assets = Vec::new();
+    for (name, source) in ASSETS {
+        let bytes = read(&root.join(source));
+        assets.push(hashed(name, &bytes, root.join(source)));
+    }
+
+    // the font awesome css links its fonts relatively, they are renamed and the links rewritten to match
+    let mut fonts = Vec::new();
+    let webfonts = root.join(FONTAWESOME).join("webfonts");
+    println!("cargo:rerun-if-changed={}", webfonts.display());
+    let entries = fs::read_dir(&webfonts).unwrap_or_else(|_| missing(&webfonts));
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let bytes = fs::read(entry.path()).unwrap();
+        fonts.push(hashed(
+            &format!("fontawesome/webfonts/{}", file_name),
+            &bytes,
+            entry.path(),
+        ));
+    }
+    fonts.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut css = String::from_utf8(read(&root.join(FONTAWESOME).join("css/all.min.css"))).unwrap();
+    for font in &fonts {
+        let file_name = font.name.rsplit('/').next().unwrap();
+        let hashed_name = font.path.rsplit('/').next().unwrap();
+        css = css.replace(
+            &format!("../webfonts/{}", file_name),
+            &format!("../webfonts/{}", hashed_name),
+        );
+    }
+    let file = out.join("all.min.css");
+    fs::write(&file, &css).unwrap();
+    assets.push(hashed("fontawesome/css/all.css", css.as_bytes(), file));
+    assets.extend(fonts);
+
+    let mut code = String::from("pub static ASSETS: &[Asset] = &[\n");
+    for asset in &assets {
+        code.push_str(&format!(
+            "    Asset {{ name: {:?}, path: {:?}, etag: {:?}, body: include_bytes!({:?}) }},\n",
+            asset.name,
+            asset.path,
+            asset.etag,
+            asset.file.display().to_string()
+        ));
+    }
+    code.push_str("];\n");
+    fs::write(out.join("assets.rs"), code).unwrap();
+}
+
+// every asset is linked from the templates, a binary without one would serve broken pages
+fn read(file: &Path) -> Vec<u8> {
+    println!("cargo:rerun-if-changed={}", file.display());
+    fs::read(file).unwrap_or_else(|_| missing(file))
+}
+
+fn missing(file: &Path) -> ! {
+    panic!(
+        "{} is missing, build the assets first (see README)",
+        file.display()
+    );
+}
+
+// css/output.css becomes css/output.0123456789abcdef.css
+fn hashed(name: &str, bytes: &[u8], file: PathBuf) -> Asset {
+    let hash = format!("{:x}", Sha256::digest(bytes));
+    let hash = &hash[..16];
+    let (stem, extension) = name.rsplit_once('.').unwrap();
+    Asset {
+        name: name.to_string(),
+        path: format!("{}.{}.{}", stem, hash, extension),
+        etag: format!("\"{}\"", hash),
+        file,
+    }
+}
@@ -1,16 +1,8 @@
 #!/bin/sh
 
-export DATABASE_URL=mysql://$DB_USERNAME:$DB_PASSWORD@$DB_HOST:$DB_PORT/$DB_NAME
-
-# Run SQLx migrations
-/usr/local/bin/sqlx db create
-echo Created database
-
-# Run SQLx migrations
-/usr/local/bin/sqlx migrate run
-echo Migrated database
+# The database is created and migrated by the application on startup
 
 # Run your application with the provided arguments
 echo Running
-./project-tracker
+./project-tracker "$@"
 echo Stopped
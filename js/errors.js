@@ -0,0 +1,7 @@
+// htmx ignores error responses by default, show the error fragments the server sends instead
+document.addEventListener('htmx:beforeSwap', (event) => {
+    if (event.detail.xhr.status >= 400 && event.detail.xhr.getResponseHeader('HX-Retarget')) {
+        event.detail.shouldSwap = true;
+        event.detail.isError = false;
+    }
+});
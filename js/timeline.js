@@ -1,70 +1,138 @@
 document.addEventListener('DOMContentLoaded', function() {
-    // Get timeline container
+    // Get timeline container and controls
     const timeline = document.getElementById('timeline');
-
-
-    // Create vertical lines for the first of every month
-    const monthLines = document.createElement('div');
-    monthLines.classList.add('month-lines');
-
-    // Loop through 12 months and create a line for each
-    for (let i = 0; i < 12; i++) {
-        const monthLine = document.createElement('div');
-        monthLine.classList.add('month-line');
-        monthLine.style.left = `${(i + 1) * (100 / 12)}%`;
-        monthLines.appendChild(monthLine);
-    }
-
-    // Add month lines to timeline
-    timeline.appendChild(monthLines);
-
+    const controls = document.getElementById('timeline-controls');
+    // prefix of the app urls, empty unless served below a path
+    const basePath = timeline.dataset.basePath;
 
     // define project colors
-    const colors = ['#e19f42', '#4299e1'];
-    
-    // Calculate timeline width (1 year) in milliseconds
-    const oneYearMs = 365 * 24 * 60 * 60 * 1000;
-
-    // Get today's date
-    const today = new Date();
-
-    // Calculate start date (1 year ago)
-    const startDate = new Date(today);
-    startDate.setFullYear(today.getFullYear() - 1);
-
-    // Filter out projects that were started over a year ago
-    const filteredProjects = projects.filter(project => new Date(project.startDate) >= startDate);
+    const colors = ['#e19f42', '#4299e1', '#48bb78', '#9f7aea', '#ed64a6', '#38b2ac'];
 
-    // Calculate number of projects and height of each project bar
-    const numProjects = filteredProjects.length;
-    const projectHeight = 100 / numProjects;
+    // Format a date as YYYY-MM-DD
+    const formatDate = (date) => {
+        const month = String(date.getMonth() + 1).padStart(2, '0');
+        const day = String(date.getDate()).padStart(2, '0');
+        return `${date.getFullYear()}-${month}-${day}`;
+    };
 
-
-    // Loop through projects and create corresponding bars
-    filteredProjects.forEach((project, index) => {
-        // Calculate project duration, width, offset, and position
-        const projectDuration = project.endDate - project.startDate;
-        const projectWidth = (projectDuration / oneYearMs) * 100;
-        const projectOffset = ((project.startDate - startDate) / oneYearMs) * 100;
-        const projectPosition = index * projectHeight;
-
-        // Create project bar
-        const projectBar = document.createElement('div');
-
-        // Add classes and styles
-        projectBar.classList.add('project-bar');
-        projectBar.style.width = `${projectWidth}%`;
-        projectBar.style.top = `${projectPosition}%`;
-        projectBar.style.left = `${projectOffset}%`;
-        projectBar.style.height = `${projectHeight}%`;
-        if (project.category == categories[0]) {
-            projectBar.style.backgroundColor = colors[0];
-        } else if (project.category == categories[1]) {
-            projectBar.style.backgroundColor = colors[1];
+    // Initialise controls with the current year and quarter
+    const today = new Date();
+    controls.year.value = today.getFullYear();
+    controls.quarter.value = Math.floor(today.getMonth() / 3) + 1;
+    const lastYear = new Date(today);
+    lastYear.setFullYear(today.getFullYear() - 1);
+    controls.start.value = formatDate(lastYear);
+    controls.end.value = formatDate(today);
+
+    // Calculate the selected date range
+    const selectedRange = () => {
+        const mode = controls.mode.value;
+        const year = parseInt(controls.year.value);
+        if (mode === 'year') {
+            return [new Date(year, 0, 1), new Date(year, 11, 31)];
+        } else if (mode === 'quarter') {
+            const quarter = parseInt(controls.quarter.value) - 1;
+            return [new Date(year, quarter * 3, 1), new Date(year, quarter * 3 + 3, 0)];
         }
-
-        // Add project bar to timeline
-        timeline.appendChild(projectBar);
-    });
-
+        return [new Date(`${controls.start.value}T00:00`), new Date(`${controls.end.value}T00:00`)];
+    };
+
+    // Show only the inputs relevant to the selected mode
+    const updateInputs = () => {
+        const mode = controls.mode.value;
+        controls.year.hidden = mode === 'custom';
+        controls.quarter.hidden = mode !== 'quarter';
+        controls.start.hidden = mode !== 'custom';
+        controls.end.hidden = mode !== 'custom';
+    };
+
+    // Move the selected range one step back or forward
+    const shiftRange = (direction) => {
+        const mode = controls.mode.value;
+        if (mode === 'year') {
+            controls.year.value = parseInt(controls.year.value) + direction;
+        } else if (mode === 'quarter') {
+            let quarter = parseInt(controls.quarter.value) + direction;
+            let year = parseInt(controls.year.value);
+            if (quarter < 1) { quarter = 4; year -= 1; }
+            if (quarter > 4) { quarter = 1; year += 1; }
+            controls.quarter.value = quarter;
+            controls.year.value = year;
+        } else {
+            const [start, end] = selectedRange();
+            const length = end - start + 24 * 60 * 60 * 1000;
+            controls.start.value = formatDate(new Date(start.getTime() + direction * length));
+            controls.end.value = formatDate(new Date(end.getTime() + direction * length));
+        }
+        loadTimeline();
+    };
+
+    // Draw gridlines and project bars returned by the server
+    const renderTimeline = (data) => {
+        timeline.innerHTML = '';
+
+        // Create vertical lines for every tick
+        const monthLines = document.createElement('div');
+        monthLines.classList.add('month-lines');
+        const startMs = new Date(data.start).getTime();
+        const rangeMs = new Date(data.end).getTime() - startMs + 24 * 60 * 60 * 1000;
+        data.ticks.forEach(tick => {
+            const monthLine = document.createElement('div');
+            monthLine.classList.add('month-line');
+            monthLine.style.left = `${(new Date(tick).getTime() - startMs) / rangeMs * 100}%`;
+            monthLines.appendChild(monthLine);
+        });
+        timeline.appendChild(monthLines);
+
+        // Calculate height of each project bar
+        const projectHeight = 100 / data.bars.length;
+
+        // Loop through projects and create corresponding bars
+        data.bars.forEach((bar, index) => {
+            const projectBar = document.createElement('div');
+            projectBar.classList.add('project-bar');
+            projectBar.title = `${bar.name} (${bar.start} - ${bar.end})`;
+            projectBar.style.width = `${bar.width}%`;
+            projectBar.style.top = `${index * projectHeight}%`;
+            projectBar.style.left = `${bar.offset}%`;
+            projectBar.style.height = `${projectHeight}%`;
+            const categoryIndex = categories.indexOf(bar.category);
+            if (categoryIndex >= 0) {
+                projectBar.style.backgroundColor = colors[categoryIndex % colors.length];
+            }
+            timeline.appendChild(projectBar);
+        });
+    };
+
+    // Fetch timeline data for the selected range
+    const loadTimeline = () => {
+        updateInputs();
+        const [start, end] = selectedRange();
+        if (isNaN(start) || isNaN(end)) {
+            return;
+        }
+        const selected = Array.from(controls.querySelectorAll('input[name="category"]:checked'))
+            .map(input => input.value);
+        if (selected.length === 0) {
+            timeline.innerHTML = '';
+            return;
+        }
+        const params = new URLSearchParams({
+            start: formatDate(start),
+            end: formatDate(end),
+            granularity: controls.granularity.value,
+        });
+        // one parameter per category, names may contain commas
+        selected.forEach(category => params.append('category', category));
+        document.getElementById('timeline-svg').href = `${basePath}/timeline.svg?${params}`;
+        document.getElementById('timeline-download').href = `${basePath}/timeline.svg?${params}&download=true`;
+        fetch(`${basePath}/timeline?${params}`)
+            .then(response => response.json())
+            .then(renderTimeline);
+    };
+
+    controls.addEventListener('change', loadTimeline);
+    document.getElementById('timeline-prev').addEventListener('click', () => shiftRange(-1));
+    document.getElementById('timeline-next').addEventListener('click', () => shiftRange(1));
+    loadTimeline();
 });
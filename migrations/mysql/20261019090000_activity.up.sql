@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS activity
+(
+    id          BIGINT  UNSIGNED PRIMARY KEY NOT NULL AUTO_INCREMENT,
+    project_id  BIGINT  UNSIGNED NOT NULL,
+    project_name TEXT   NOT NULL,
+    category    TEXT    NOT NULL,
+    event       TEXT    NOT NULL,
+    created_at  DATETIME NOT NULL
+);
@@ -0,0 +1,20 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS webhooks
+(
+    id          BIGINT  UNSIGNED PRIMARY KEY NOT NULL AUTO_INCREMENT,
+    url         TEXT    NOT NULL,
+    secret      TEXT    NOT NULL,
+    events      TEXT    NOT NULL DEFAULT "",
+    creation_date DATE NOT NULL DEFAULT CURRENT_DATE
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries
+(
+    id          BIGINT  UNSIGNED PRIMARY KEY NOT NULL AUTO_INCREMENT,
+    webhook_id  BIGINT  UNSIGNED NOT NULL,
+    event       TEXT    NOT NULL,
+    attempt     BIGINT  UNSIGNED NOT NULL,
+    status_code BIGINT  UNSIGNED,
+    error       TEXT    NOT NULL DEFAULT "",
+    delivered_at DATETIME NOT NULL
+);
@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS api_tokens
+(
+    id          BIGINT  UNSIGNED PRIMARY KEY NOT NULL AUTO_INCREMENT,
+    user_id     BIGINT  UNSIGNED NOT NULL,
+    name        VARCHAR(255) NOT NULL,
+    token_hash  CHAR(64) NOT NULL UNIQUE,
+    scopes      VARCHAR(32) NOT NULL,
+    created_at  DATETIME NOT NULL,
+    last_used   DATETIME NULL,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
@@ -0,0 +1,13 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS projects
+(
+    id          BIGSERIAL PRIMARY KEY NOT NULL,
+    name        TEXT    NOT NULL DEFAULT 'New Project',
+    category    TEXT    NOT NULL,
+    position    BIGINT NOT NULL,
+    status      BIGINT NOT NULL DEFAULT 0,
+    notes       TEXT    NOT NULL DEFAULT '',
+    creation_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    start_date  DATE NOT NULL DEFAULT CURRENT_DATE,
+    completion_date DATE NOT NULL DEFAULT CURRENT_DATE
+);
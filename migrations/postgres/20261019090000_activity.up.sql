@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS activity
+(
+    id          BIGSERIAL PRIMARY KEY NOT NULL,
+    project_id  BIGINT NOT NULL,
+    project_name TEXT   NOT NULL,
+    category    TEXT    NOT NULL,
+    event       TEXT    NOT NULL,
+    created_at  TIMESTAMP NOT NULL
+);
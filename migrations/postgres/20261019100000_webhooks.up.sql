@@ -0,0 +1,20 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS webhooks
+(
+    id          BIGSERIAL PRIMARY KEY NOT NULL,
+    url         TEXT    NOT NULL,
+    secret      TEXT    NOT NULL,
+    events      TEXT    NOT NULL DEFAULT '',
+    creation_date DATE NOT NULL DEFAULT CURRENT_DATE
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries
+(
+    id          BIGSERIAL PRIMARY KEY NOT NULL,
+    webhook_id  BIGINT NOT NULL,
+    event       TEXT    NOT NULL,
+    attempt     BIGINT NOT NULL,
+    status_code BIGINT,
+    error       TEXT    NOT NULL DEFAULT '',
+    delivered_at TIMESTAMP NOT NULL
+);
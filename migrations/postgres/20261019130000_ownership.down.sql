@@ -0,0 +1,9 @@
+DROP TABLE IF EXISTS category_shares;
+DROP TABLE IF EXISTS categories;
+
+ALTER TABLE webhooks
+    DROP COLUMN owner_id;
+ALTER TABLE activity
+    DROP COLUMN owner_id;
+ALTER TABLE projects
+    DROP COLUMN owner_id;
@@ -0,0 +1,40 @@
+-- Add migration script here
+ALTER TABLE projects
+    ADD COLUMN owner_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE activity
+    ADD COLUMN owner_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE webhooks
+    ADD COLUMN owner_id BIGINT NOT NULL DEFAULT 0;
+
+-- existing data belongs to the first user, owner 0 is claimed by the first user to sign up
+UPDATE projects SET owner_id = COALESCE(( SELECT MIN(id) FROM users ), 0);
+UPDATE activity SET owner_id = COALESCE(( SELECT MIN(id) FROM users ), 0);
+UPDATE webhooks SET owner_id = COALESCE(( SELECT MIN(id) FROM users ), 0);
+
+CREATE TABLE IF NOT EXISTS categories
+(
+    id          BIGSERIAL PRIMARY KEY NOT NULL,
+    owner_id    BIGINT NOT NULL,
+    name        VARCHAR(255) NOT NULL,
+    UNIQUE (owner_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS category_shares
+(
+    id          BIGSERIAL PRIMARY KEY NOT NULL,
+    category_id BIGINT NOT NULL,
+    user_id     BIGINT NOT NULL,
+    access      VARCHAR(8) NOT NULL DEFAULT 'read',
+    UNIQUE (category_id, user_id),
+    FOREIGN KEY (category_id) REFERENCES categories (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+-- default categories of existing users, plus any category already in use
+INSERT INTO categories ( owner_id, name )
+SELECT id, 'Personal' FROM users;
+INSERT INTO categories ( owner_id, name )
+SELECT id, 'Professional' FROM users;
+INSERT INTO categories ( owner_id, name )
+SELECT DISTINCT owner_id, category FROM projects WHERE owner_id > 0
+ON CONFLICT ( owner_id, name ) DO NOTHING;
@@ -0,0 +1,13 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS projects
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name        TEXT    NOT NULL DEFAULT 'New Project',
+    category    TEXT    NOT NULL,
+    position    INTEGER NOT NULL,
+    status      INTEGER NOT NULL DEFAULT 0,
+    notes       TEXT    NOT NULL DEFAULT '',
+    creation_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    start_date  DATE NOT NULL DEFAULT CURRENT_DATE,
+    completion_date DATE NOT NULL DEFAULT CURRENT_DATE
+);
@@ -0,0 +1,20 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS webhooks
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    url         TEXT    NOT NULL,
+    secret      TEXT    NOT NULL,
+    events      TEXT    NOT NULL DEFAULT '',
+    creation_date DATE NOT NULL DEFAULT CURRENT_DATE
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    webhook_id  INTEGER NOT NULL,
+    event       TEXT    NOT NULL,
+    attempt     INTEGER NOT NULL,
+    status_code INTEGER,
+    error       TEXT    NOT NULL DEFAULT '',
+    delivered_at DATETIME NOT NULL
+);
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE projects
+    ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
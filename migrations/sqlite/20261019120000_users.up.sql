@@ -0,0 +1,17 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS users
+(
+    id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    username    VARCHAR(255) NOT NULL UNIQUE,
+    password_hash TEXT  NOT NULL,
+    creation_date DATE NOT NULL DEFAULT CURRENT_DATE
+);
+
+CREATE TABLE IF NOT EXISTS sessions
+(
+    token_hash  CHAR(64) PRIMARY KEY NOT NULL,
+    user_id     INTEGER NOT NULL,
+    created_at  DATETIME NOT NULL,
+    expires_at  DATETIME NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
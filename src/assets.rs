@@ -0,0 +1,78 @@
+use crate::urls;
+
+// static file compiled into the binary by build.rs
+pub struct Asset {
+    // name templates link it by, e.g. "css/output.css"
+    pub name: &'static str,
+    // served path with the content hash, e.g. "css/output.0123456789abcdef.css"
+    pub path: &'static str,
+    pub etag: &'static str,
+    pub body: &'static [u8],
+}
+
+include!(concat!(env!("OUT_DIR"), "/assets.rs"));
+
+// the name changes with the content, so browsers may keep a file for a year
+pub const CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+pub fn get(path: &str) -> Option<&'static Asset> {
+    ASSETS.iter().find(|asset| asset.path == path)
+}
+
+// url of an asset for templates, unknown names are linked as they are and not found
+pub fn url(name: &str) -> String {
+    let path = ASSETS
+        .iter()
+        .find(|asset| asset.name == name)
+        .map_or(name, |asset| asset.path);
+    urls::url(&format!("/static/{}", path))
+}
+
+// If-None-Match is "*" or a comma separated list of etags, compared weakly as revalidation only needs equal content
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    if_none_match
+        .split(',')
+        .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+pub fn content_type(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("woff2") => "font/woff2",
+        Some("woff") => "font/woff",
+        Some("ttf") => "font/ttf",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn etags_match_weakly() {
+        assert!(etag_matches("\"abc\"", "\"abc\""));
+        assert!(etag_matches("W/\"abc\"", "\"abc\""));
+        assert!(etag_matches("\"abc\"", "W/\"abc\""));
+        assert!(etag_matches("\"x\", W/\"abc\"", "\"abc\""));
+        assert!(etag_matches("*", "\"abc\""));
+        assert!(!etag_matches("\"abcd\"", "\"abc\""));
+        assert!(!etag_matches("abc", "\"abc\""));
+        assert!(!etag_matches("", "\"abc\""));
+    }
+
+    #[test]
+    fn content_types_follow_the_extension() {
+        assert_eq!(content_type("output.css"), "text/css; charset=utf-8");
+        assert_eq!(
+            content_type("js/htmx.min.js"),
+            "text/javascript; charset=utf-8"
+        );
+        assert_eq!(content_type("fa-solid-900.woff2"), "font/woff2");
+        assert_eq!(content_type("README"), "application/octet-stream");
+    }
+}
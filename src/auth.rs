@@ -0,0 +1,405 @@
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Redirect, Response};
+use chrono::{Days, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::csrf;
+use crate::db;
+use crate::error;
+use crate::models;
+use crate::state::AppState;
+use crate::urls;
+
+pub const SESSION_COOKIE: &str = "session";
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+const SESSION_DAYS: u64 = 30;
+// verified instead of a password hash when the username does not exist, with the parameters of hash_password,
+// so a failed sign in takes as long either way and does not reveal which usernames exist
+const DUMMY_PASSWORD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$DJaSpnO6fW8+baJny+Drig$R0aKhQ+sl/LyH9Vex2awVajhG3w+4Gr1Bd7R74E4Yt8";
+
+// argon2id hash in PHC string format, including the random salt
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|err| anyhow::anyhow!("Could not hash password: {}", err))?;
+    Ok(hash.to_string())
+}
+
+pub fn verify_password(password_hash: &str, password: &str) -> bool {
+    PasswordHash::new(password_hash).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+// user with username and password, the password is checked even without a user
+pub async fn login(
+    db: &db::Db,
+    username: &str,
+    password: &str,
+) -> anyhow::Result<Option<models::User>> {
+    match db.get_user_by_username(username).await? {
+        Some(user) if verify_password(&user.password_hash, password) => Ok(Some(user)),
+        Some(_) => Ok(None),
+        None => {
+            verify_password(DUMMY_PASSWORD_HASH, password);
+            Ok(None)
+        }
+    }
+}
+
+// session and api tokens are stored hashed so a database leak does not expose live tokens
+pub fn token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// compare secrets without stopping at the first difference, so timing does not reveal how much matched
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+// calendar apps and feed readers cannot sign in, their feed urls carry a token of its user, "<user id>.<secret>"
+pub fn calendar_token(user_id: u64) -> String {
+    format!("{}.{}", user_id, hex::encode(rand::random::<[u8; 32]>()))
+}
+
+// owner of a calendar feed token, only the hash of the token is stored
+pub async fn calendar_user(db: &db::Db, token: &str) -> anyhow::Result<Option<models::User>> {
+    let Some(user_id) = token
+        .split_once('.')
+        .and_then(|(id, _)| id.parse::<u64>().ok())
+    else {
+        return Ok(None);
+    };
+    match db.get_calendar_token_hash(user_id).await? {
+        Some(expected) if constant_time_eq(&expected, &token_hash(token)) => {
+            db.get_user(user_id).await
+        }
+        _ => Ok(None),
+    }
+}
+
+pub fn session_cookie(token: &str, max_age: u64, secure: bool) -> String {
+    format!(
+        "{}={}; Path={}; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        SESSION_COOKIE,
+        token,
+        // only sent to this app when other apps share the domain
+        if urls::base_path().is_empty() {
+            "/"
+        } else {
+            urls::base_path()
+        },
+        max_age,
+        if secure { "; Secure" } else { "" }
+    )
+}
+
+// start a session for the user, returns the Set-Cookie value
+pub async fn start_session(db: &db::Db, user_id: u64, secure: bool) -> anyhow::Result<String> {
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    let expires_at = Utc::now().naive_utc() + Days::new(SESSION_DAYS);
+    db.add_session(&token_hash(&token), user_id, expires_at)
+        .await?;
+    Ok(session_cookie(&token, SESSION_DAYS * 24 * 60 * 60, secure))
+}
+
+// value of a request cookie
+pub fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+// bearer token of an api request
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+// feed token of a calendar or atom feed request, other routes cannot be read with it
+fn feed_token(state: &AppState, request: &Request) -> Option<String> {
+    let feed = match request.uri().path() {
+        "/calendar.ics" => state.features.calendar,
+        "/feed/completed.atom" | "/feed/activity.atom" => state.features.feeds,
+        _ => false,
+    };
+    if !feed || request.method() != Method::GET {
+        return None;
+    }
+    serde_urlencoded::from_str::<Vec<(String, String)>>(request.uri().query().unwrap_or_default())
+        .ok()?
+        .into_iter()
+        .find(|(key, _)| key == "token")
+        .map(|(_, token)| token)
+}
+
+// scope an api token needs for a route, reads only need read access
+fn required_scope(method: &Method, path: &str) -> models::Scope {
+    if path == "/backup" || path == "/restore" || path.starts_with("/tokens") {
+        models::Scope::Admin
+    } else if method == Method::GET || method == Method::HEAD {
+        models::Scope::Read
+    } else {
+        models::Scope::Write
+    }
+}
+
+// authorize a scripted request with a personal api token instead of a session
+async fn authorize_token(
+    state: &AppState,
+    token: &str,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, error::AppError> {
+    let Some(token) = state.db.use_api_token(&token_hash(token)).await? else {
+        return Ok((
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Invalid token",
+        )
+            .into_response());
+    };
+    let scope = required_scope(request.method(), request.uri().path());
+    if !token.allows(scope) {
+        let message = format!("Token needs the {} scope", scope.as_str());
+        return Ok((StatusCode::FORBIDDEN, message).into_response());
+    }
+    let Some(user) = state.db.get_user(token.user_id).await? else {
+        return Ok((
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Invalid token",
+        )
+            .into_response());
+    };
+    // scripts cannot be tricked into sending requests, they need no csrf token
+    request.extensions_mut().insert(user);
+    request.extensions_mut().insert(csrf::CsrfToken::default());
+    Ok(next.run(request).await)
+}
+
+// authorize a calendar or atom feed request with the feed token of a user, feeds only read
+async fn authorize_feed(
+    state: &AppState,
+    token: &str,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, error::AppError> {
+    let Some(user) = calendar_user(&state.db, token).await? else {
+        return Err(error::AppError::NotFound);
+    };
+    request.extensions_mut().insert(user);
+    request.extensions_mut().insert(csrf::CsrfToken::default());
+    Ok(next.run(request).await)
+}
+
+// middleware for protected routes, the signed in user and csrf token are added to the request extensions
+pub async fn require_login(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, error::AppError> {
+    if let Some(token) = bearer_token(request.headers()).filter(|_| state.features.api_tokens) {
+        let token = token.to_string();
+        return authorize_token(&state, &token, request, next).await;
+    }
+
+    let session = cookie(request.headers(), SESSION_COOKIE).map(str::to_string);
+    let user = match &session {
+        Some(token) => state.db.get_session_user(&token_hash(token)).await?,
+        None => None,
+    };
+    // calendar apps and feed readers cannot sign in, their links carry the feed token of the user
+    if user.is_none() {
+        if let Some(token) = feed_token(&state, &request) {
+            return authorize_feed(&state, &token, request, next).await;
+        }
+    }
+    match (user, session) {
+        (Some(user), Some(session)) => {
+            // browsers send the session cookie along with forms of other sites, so changes need the csrf token
+            let csrf_token = csrf::CsrfToken::for_session(&session);
+            if !csrf::is_safe(request.method()) {
+                match csrf::verify(&csrf_token, request).await {
+                    Ok(verified) => request = verified,
+                    Err(rejection) => return Ok(rejection),
+                }
+            }
+            request.extensions_mut().insert(user);
+            request.extensions_mut().insert(csrf_token);
+            Ok(next.run(request).await)
+        }
+        // htmx requests would swap the login page into a fragment, let htmx redirect instead
+        _ if request.headers().contains_key("HX-Request") => Ok((
+            StatusCode::UNAUTHORIZED,
+            [("HX-Redirect", urls::url("/login"))],
+        )
+            .into_response()),
+        _ => Ok(Redirect::to(&urls::url("/login")).into_response()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::Scope;
+
+    fn api_token(scopes: &str) -> models::ApiToken {
+        models::ApiToken {
+            id: 1,
+            user_id: 1,
+            name: "script".to_string(),
+            scopes: scopes.to_string(),
+            created_at: Utc::now().naive_utc(),
+            last_used: None,
+        }
+    }
+
+    #[test]
+    fn reads_need_the_read_scope() {
+        assert_eq!(required_scope(&Method::GET, "/"), Scope::Read);
+        assert_eq!(required_scope(&Method::HEAD, "/timeline"), Scope::Read);
+        assert_eq!(required_scope(&Method::GET, "/export.csv"), Scope::Read);
+        assert_eq!(required_scope(&Method::GET, "/list/1"), Scope::Read);
+    }
+
+    #[test]
+    fn changes_need_the_write_scope() {
+        assert_eq!(required_scope(&Method::POST, "/add"), Scope::Write);
+        assert_eq!(required_scope(&Method::POST, "/start"), Scope::Write);
+        assert_eq!(required_scope(&Method::POST, "/complete"), Scope::Write);
+        assert_eq!(required_scope(&Method::POST, "/delete"), Scope::Write);
+        assert_eq!(required_scope(&Method::POST, "/update_notes"), Scope::Write);
+    }
+
+    #[test]
+    fn backups_and_tokens_need_the_admin_scope() {
+        assert_eq!(required_scope(&Method::GET, "/backup"), Scope::Admin);
+        assert_eq!(required_scope(&Method::POST, "/restore"), Scope::Admin);
+        assert_eq!(required_scope(&Method::GET, "/tokens"), Scope::Admin);
+        assert_eq!(required_scope(&Method::POST, "/tokens/add"), Scope::Admin);
+        assert_eq!(
+            required_scope(&Method::POST, "/tokens/delete"),
+            Scope::Admin
+        );
+    }
+
+    #[test]
+    fn scopes_include_the_ones_below() {
+        let read = api_token("read");
+        assert!(read.allows(Scope::Read));
+        assert!(!read.allows(Scope::Write));
+        assert!(!read.allows(Scope::Admin));
+
+        let write = api_token("write");
+        assert!(write.allows(Scope::Read));
+        assert!(write.allows(Scope::Write));
+        assert!(!write.allows(Scope::Admin));
+
+        assert!(api_token("read,admin").allows(Scope::Write));
+        assert!(!api_token("").allows(Scope::Read));
+        assert!(!api_token("root, write").allows(Scope::Admin));
+    }
+
+    #[test]
+    fn scope_names_round_trip() {
+        for scope in Scope::ALL {
+            assert_eq!(Scope::parse(scope.as_str()), Some(scope));
+        }
+        assert_eq!(Scope::parse("Admin"), None);
+    }
+
+    #[test]
+    fn bearer_token_is_read_from_the_authorization_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+        headers.insert(header::AUTHORIZATION, "Bearer abc ".parse().unwrap());
+        assert_eq!(bearer_token(&headers), Some("abc"));
+        headers.insert(header::AUTHORIZATION, "Basic abc".parse().unwrap());
+        assert_eq!(bearer_token(&headers), None);
+    }
+
+    #[test]
+    fn token_hashes_are_sha256() {
+        assert_eq!(
+            token_hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(constant_time_eq(&token_hash("a"), &token_hash("a")));
+        assert!(!constant_time_eq(&token_hash("a"), &token_hash("b")));
+        assert!(!constant_time_eq("abc", "abcd"));
+    }
+
+    #[test]
+    fn dummy_hash_is_a_valid_argon2_hash() {
+        let hash = PasswordHash::new(DUMMY_PASSWORD_HASH).unwrap();
+        let real = hash_password("password").unwrap();
+        let real = PasswordHash::new(&real).unwrap();
+        assert_eq!(hash.algorithm, real.algorithm);
+        assert_eq!(hash.params, real.params);
+        assert!(!verify_password(DUMMY_PASSWORD_HASH, ""));
+    }
+
+    #[tokio::test]
+    async fn login_checks_username_and_password() {
+        let db = db::test_db().await;
+        let hash = hash_password("password1").unwrap();
+        let id = db
+            .add_first_user("alice", &hash, &[])
+            .await
+            .unwrap()
+            .unwrap();
+
+        let user = login(&db, "alice", "password1").await.unwrap().unwrap();
+        assert_eq!(user.id, id);
+        assert!(login(&db, "alice", "password2").await.unwrap().is_none());
+        assert!(login(&db, "bob", "password1").await.unwrap().is_none());
+        assert!(login(&db, "Alice", "password1").await.unwrap().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn only_one_first_user_is_set_up() {
+        let db = db::test_db().await;
+        let attempts = (0..6).map(|index| {
+            let db = db.clone();
+            tokio::spawn(async move {
+                db.add_first_user(&format!("user{}", index), "hash", &[])
+                    .await
+                    .unwrap()
+            })
+        });
+        let mut created = 0;
+        for attempt in attempts.collect::<Vec<_>>() {
+            if attempt.await.unwrap().is_some() {
+                created += 1;
+            }
+        }
+        assert_eq!(created, 1);
+        assert_eq!(db.get_users().await.unwrap().len(), 1);
+        assert!(db
+            .add_first_user("late", "hash", &[])
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
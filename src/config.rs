@@ -0,0 +1,528 @@
+use serde::Deserialize;
+use std::env;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::validate;
+
+// file read when CONFIG_FILE is not set, it is optional
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+// settings of the server, read from a TOML file and overridden by environment variables
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // address and port the server listens on
+    pub bind_address: String,
+    // path prefix when served behind a reverse proxy, e.g. "/projects"
+    pub base_path: String,
+    // time zone of the dates, e.g. "Europe/Berlin", defaults to the time zone of the system
+    pub timezone: Option<String>,
+    // number of completed projects loaded per block
+    pub page_size: u64,
+    // the projects of a user are saved here before a restore replaces them
+    pub backup_dir: Option<PathBuf>,
+    // only send the session cookie over https, off by default so a plain http setup can sign in
+    pub secure_cookies: bool,
+    // categories every new user starts with
+    pub default_categories: Vec<String>,
+    pub database: DatabaseConfig,
+    pub features: Features,
+    pub webhooks: WebhookConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind_address: "0.0.0.0:4200".to_string(),
+            base_path: String::new(),
+            timezone: None,
+            page_size: 10,
+            backup_dir: None,
+            secure_cookies: false,
+            default_categories: vec!["Personal".to_string(), "Professional".to_string()],
+            database: DatabaseConfig::default(),
+            features: Features::default(),
+            webhooks: WebhookConfig::default(),
+        }
+    }
+}
+
+// either a connection url, or the parts of a mysql url
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub host: Option<String>,
+    pub port: u16,
+    pub name: Option<String>,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            url: None,
+            username: None,
+            password: None,
+            host: None,
+            port: 3306,
+            name: None,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    pub fn url(&self) -> Option<String> {
+        if let Some(url) = &self.url {
+            return Some(url.clone());
+        }
+        match (&self.username, &self.host, &self.name) {
+            (Some(username), Some(host), Some(name)) => Some(format!(
+                "mysql://{}:{}@{}:{}/{}",
+                username,
+                self.password.as_deref().unwrap_or_default(),
+                host,
+                self.port,
+                name
+            )),
+            _ => None,
+        }
+    }
+}
+
+// optional parts of the tracker, disabled features have no routes and no links
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Features {
+    pub webhooks: bool,
+    pub api_tokens: bool,
+    pub feeds: bool,
+    pub calendar: bool,
+    pub import: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Features {
+            webhooks: true,
+            api_tokens: true,
+            feeds: true,
+            calendar: true,
+            import: true,
+        }
+    }
+}
+
+// webhook targets besides public hosts, e.g. home automation on the local network
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    // any host, including private addresses and local names
+    pub allow_private: bool,
+    // names or addresses allowed even if they are private
+    pub allowed_hosts: Vec<String>,
+}
+
+impl WebhookConfig {
+    pub fn allows(&self, host: &str) -> bool {
+        self.allow_private
+            || self
+                .allowed_hosts
+                .iter()
+                .any(|allowed| host_name(allowed) == host_name(host))
+    }
+}
+
+// hosts compare without case, ipv6 brackets and the trailing dot of a fully qualified name
+fn host_name(host: &str) -> String {
+    host.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.')
+        .to_lowercase()
+}
+
+impl Config {
+    // read the config file and environment, every invalid setting is reported at once
+    pub fn load() -> anyhow::Result<Config> {
+        let mut config = match env::var("CONFIG_FILE") {
+            Ok(path) => Config::read(Path::new(&path))?,
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Config::read(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            Err(_) => Config::default(),
+        };
+
+        let mut errors = Vec::new();
+        config.override_from_env(&mut errors);
+        config.validate(&mut errors);
+        if !errors.is_empty() {
+            anyhow::bail!("Invalid configuration:\n  {}", errors.join("\n  "));
+        }
+        Ok(config)
+    }
+
+    // print the effective settings, without secrets
+    pub fn report(&self) {
+        let database = match &self.database.url {
+            Some(url) => url
+                .split_once('@')
+                .map_or(url.as_str(), |(_, host)| host)
+                .to_string(),
+            None => format!(
+                "{}:{}/{}",
+                self.database.host.as_deref().unwrap_or_default(),
+                self.database.port,
+                self.database.name.as_deref().unwrap_or_default()
+            ),
+        };
+        println!("Configuration:");
+        println!("  bind_address = {}", self.bind_address);
+        println!("  base_path = {:?}", self.base_path);
+        println!(
+            "  timezone = {}",
+            self.timezone.as_deref().unwrap_or("system")
+        );
+        println!("  page_size = {}", self.page_size);
+        println!(
+            "  backup_dir = {}",
+            self.backup_dir
+                .as_ref()
+                .map_or("none".to_string(), |dir| dir.display().to_string())
+        );
+        println!("  secure_cookies = {}", self.secure_cookies);
+        println!(
+            "  default_categories = {}",
+            self.default_categories.join(", ")
+        );
+        println!("  database = {}", database);
+        println!("  features = {:?}", self.features);
+        println!("  webhooks = {:?}", self.webhooks);
+    }
+
+    fn read(path: &Path) -> anyhow::Result<Config> {
+        let contents = std::fs::read_to_string(path).map_err(|err| {
+            anyhow::anyhow!("Could not read config file {}: {}", path.display(), err)
+        })?;
+        basic_toml::from_str(&contents)
+            .map_err(|err| anyhow::anyhow!("Invalid config file {}: {}", path.display(), err))
+    }
+
+    fn override_from_env(&mut self, errors: &mut Vec<String>) {
+        self.override_from(&|name| env::var(name).ok(), errors);
+    }
+
+    // variables are looked up through `var`, tests pass their own instead of changing the process environment
+    fn override_from(&mut self, var: &dyn Fn(&str) -> Option<String>, errors: &mut Vec<String>) {
+        override_string(var, "BIND_ADDRESS", &mut self.bind_address);
+        override_string(var, "BASE_PATH", &mut self.base_path);
+        override_option(var, "TZ", &mut self.timezone);
+        override_parsed(var, "PAGE_SIZE", &mut self.page_size, errors);
+        if let Some(value) = var("BACKUP_DIR") {
+            self.backup_dir = Some(value)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from);
+        }
+        override_parsed(var, "SECURE_COOKIES", &mut self.secure_cookies, errors);
+        if let Some(value) = var("DEFAULT_CATEGORIES") {
+            self.default_categories = value
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .collect();
+        }
+
+        override_option(var, "DATABASE_URL", &mut self.database.url);
+        override_option(var, "DB_USERNAME", &mut self.database.username);
+        override_option(var, "DB_PASSWORD", &mut self.database.password);
+        override_option(var, "DB_HOST", &mut self.database.host);
+        override_parsed(var, "DB_PORT", &mut self.database.port, errors);
+        override_option(var, "DB_NAME", &mut self.database.name);
+
+        override_parsed(var, "FEATURE_WEBHOOKS", &mut self.features.webhooks, errors);
+        override_parsed(
+            var,
+            "FEATURE_API_TOKENS",
+            &mut self.features.api_tokens,
+            errors,
+        );
+        override_parsed(var, "FEATURE_FEEDS", &mut self.features.feeds, errors);
+        override_parsed(var, "FEATURE_CALENDAR", &mut self.features.calendar, errors);
+        override_parsed(var, "FEATURE_IMPORT", &mut self.features.import, errors);
+
+        override_parsed(
+            var,
+            "WEBHOOK_ALLOW_PRIVATE",
+            &mut self.webhooks.allow_private,
+            errors,
+        );
+        if let Some(value) = var("WEBHOOK_ALLOWED_HOSTS") {
+            self.webhooks.allowed_hosts = value
+                .split(',')
+                .map(|host| host.trim().to_string())
+                .filter(|host| !host.is_empty())
+                .collect();
+        }
+    }
+
+    fn validate(&mut self, errors: &mut Vec<String>) {
+        if self.bind_address.parse::<SocketAddr>().is_err() {
+            errors.push(format!(
+                "bind_address {:?} is not an address like 0.0.0.0:4200",
+                self.bind_address
+            ));
+        }
+
+        // "/" and a trailing slash mean the same as no slash
+        self.base_path = self.base_path.trim_end_matches('/').to_string();
+        // it ends up unescaped in urls, so only plain path characters are allowed
+        if !self.base_path.is_empty()
+            && (!self.base_path.starts_with('/')
+                || !self
+                    .base_path
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "/-._~".contains(c)))
+        {
+            errors.push(format!(
+                "base_path {:?} has to be a path like /projects",
+                self.base_path
+            ));
+        }
+
+        if let Some(timezone) = &self.timezone {
+            let zoneinfo = Path::new("/usr/share/zoneinfo");
+            if timezone.is_empty()
+                || timezone.contains("..")
+                || (zoneinfo.exists() && !zoneinfo.join(timezone).is_file())
+            {
+                errors.push(format!("timezone {:?} is not a known time zone", timezone));
+            }
+        }
+
+        if !(1..=1000).contains(&self.page_size) {
+            errors.push(format!(
+                "page_size {} has to be between 1 and 1000",
+                self.page_size
+            ));
+        }
+
+        if let Some(backup_dir) = &self.backup_dir {
+            if let Err(err) = std::fs::create_dir_all(backup_dir) {
+                errors.push(format!(
+                    "backup_dir {} cannot be created: {}",
+                    backup_dir.display(),
+                    err
+                ));
+            }
+        }
+
+        if self.default_categories.is_empty() {
+            errors.push("default_categories needs at least one category".to_string());
+        }
+        for name in &self.default_categories {
+            if let Err(message) = validate::category_name(name) {
+                errors.push(format!("default_categories: {}", message));
+            }
+        }
+
+        for host in &self.webhooks.allowed_hosts {
+            let valid = reqwest::Url::parse(&format!("http://{}/", host.trim())).is_ok_and(|url| {
+                url.host_str().is_some_and(|name| !name.is_empty()) && url.path() == "/"
+            });
+            if !valid {
+                errors.push(format!(
+                    "webhooks.allowed_hosts {:?} is not a host name or address",
+                    host
+                ));
+            }
+        }
+
+        match self.database.url() {
+            Some(url)
+                if ["mysql:", "postgres:", "postgresql:", "sqlite:"]
+                    .iter()
+                    .any(|scheme| url.starts_with(scheme)) => {}
+            Some(_) => errors
+                .push("database url has to start with mysql:, postgres: or sqlite:".to_string()),
+            None => errors
+                .push("database url, or database username, host and name are required".to_string()),
+        }
+    }
+}
+
+fn override_string(var: &dyn Fn(&str) -> Option<String>, name: &str, target: &mut String) {
+    if let Some(value) = var(name) {
+        *target = value;
+    }
+}
+
+// empty variables unset the setting
+fn override_option(var: &dyn Fn(&str) -> Option<String>, name: &str, target: &mut Option<String>) {
+    if let Some(value) = var(name) {
+        *target = Some(value).filter(|value| !value.is_empty());
+    }
+}
+
+fn override_parsed<T: FromStr>(
+    var: &dyn Fn(&str) -> Option<String>,
+    name: &str,
+    target: &mut T,
+    errors: &mut Vec<String>,
+) {
+    if let Some(value) = var(name) {
+        match value.parse() {
+            Ok(value) => *target = value,
+            Err(_) => errors.push(format!("{} has an invalid value {:?}", name, value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // config from a file and variables, with the errors of both steps
+    fn load(toml: &str, vars: &[(&str, &str)]) -> (Config, Vec<String>) {
+        let mut config: Config = basic_toml::from_str(toml).unwrap();
+        let var = |name: &str| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        };
+        let mut errors = Vec::new();
+        config.override_from(&var, &mut errors);
+        config.validate(&mut errors);
+        (config, errors)
+    }
+
+    const DATABASE: &str = "[database]\nurl = \"sqlite://projects.db\"\n";
+
+    #[test]
+    fn defaults_need_only_a_database() {
+        let (config, errors) = load(DATABASE, &[]);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(config.bind_address, "0.0.0.0:4200");
+        assert_eq!(config.base_path, "");
+        assert_eq!(config.timezone, None);
+        assert_eq!(config.page_size, 10);
+        assert!(!config.secure_cookies);
+        assert_eq!(config.default_categories, ["Personal", "Professional"]);
+
+        let (_, errors) = load("", &[]);
+        assert_eq!(
+            errors,
+            ["database url, or database username, host and name are required"]
+        );
+    }
+
+    #[test]
+    fn variables_override_the_file() {
+        let toml = format!(
+            "bind_address = \"127.0.0.1:8000\"\npage_size = 20\ntimezone = \"Europe/Berlin\"\nsecure_cookies = true\n{}",
+            DATABASE
+        );
+        let (config, errors) = load(&toml, &[]);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(config.page_size, 20);
+        assert_eq!(config.timezone.as_deref(), Some("Europe/Berlin"));
+        assert!(config.secure_cookies);
+
+        let (config, errors) = load(
+            &toml,
+            &[
+                ("PAGE_SIZE", "30"),
+                ("TZ", "UTC"),
+                ("SECURE_COOKIES", "false"),
+                ("DEFAULT_CATEGORIES", "Work, Home"),
+                ("DATABASE_URL", "postgres://localhost/projects"),
+            ],
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(config.bind_address, "127.0.0.1:8000");
+        assert_eq!(config.page_size, 30);
+        assert_eq!(config.timezone.as_deref(), Some("UTC"));
+        assert!(!config.secure_cookies);
+        assert_eq!(config.default_categories, ["Work", "Home"]);
+        assert_eq!(
+            config.database.url().as_deref(),
+            Some("postgres://localhost/projects")
+        );
+
+        // an empty variable unsets the setting of the file
+        let (config, _) = load(&toml, &[("TZ", "")]);
+        assert_eq!(config.timezone, None);
+    }
+
+    #[test]
+    fn mysql_url_is_built_from_its_parts() {
+        let vars = [
+            ("DB_USERNAME", "tracker"),
+            ("DB_PASSWORD", "secret"),
+            ("DB_HOST", "db"),
+            ("DB_NAME", "projects"),
+        ];
+        let (config, errors) = load("", &vars);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            config.database.url().as_deref(),
+            Some("mysql://tracker:secret@db:3306/projects")
+        );
+    }
+
+    #[test]
+    fn base_path_is_normalized() {
+        for (base_path, normalized) in [("/projects/", "/projects"), ("/", ""), ("", "")] {
+            let (config, errors) = load(DATABASE, &[("BASE_PATH", base_path)]);
+            assert!(errors.is_empty(), "{:?}", errors);
+            assert_eq!(config.base_path, normalized);
+        }
+        for base_path in ["projects", "/my projects", "/a?b"] {
+            let (_, errors) = load(DATABASE, &[("BASE_PATH", base_path)]);
+            assert_eq!(errors.len(), 1, "{}", base_path);
+            assert!(errors[0].starts_with("base_path"));
+        }
+    }
+
+    #[test]
+    fn invalid_values_are_all_reported() {
+        let toml = "bind_address = \"localhost\"\npage_size = 0\ndefault_categories = []\n\
+            [database]\nurl = \"ftp://projects\"\n\
+            [webhooks]\nallowed_hosts = [\"hooks/path\"]\n";
+        let (_, errors) = load(toml, &[("TZ", "../etc/passwd")]);
+        let settings: Vec<_> = errors
+            .iter()
+            .map(|error| error.split_whitespace().next().unwrap())
+            .collect();
+        assert_eq!(
+            settings,
+            [
+                "bind_address",
+                "timezone",
+                "page_size",
+                "default_categories",
+                "webhooks.allowed_hosts",
+                "database"
+            ]
+        );
+
+        let (_, errors) = load(
+            DATABASE,
+            &[("PAGE_SIZE", "many"), ("SECURE_COOKIES", "yes")],
+        );
+        assert_eq!(
+            errors,
+            [
+                "PAGE_SIZE has an invalid value \"many\"",
+                "SECURE_COOKIES has an invalid value \"yes\""
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_settings_are_rejected() {
+        assert!(basic_toml::from_str::<Config>("colour = \"blue\"").is_err());
+        assert!(basic_toml::from_str::<Config>("[features]\nchat = true").is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use tokio_stream::StreamExt;
+
+use crate::auth;
+use crate::validate;
+
+pub const CSRF_FIELD: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+// largest form body that is read to find the token, import confirmations carry a whole upload
+const FORM_LIMIT: usize = validate::MAX_UPLOAD_SIZE;
+
+// token that has to be sent with every state changing request of a session
+// derived from the session token, so it needs no storage and changes with every sign in
+#[derive(Clone, Debug, Default)]
+pub struct CsrfToken(String);
+
+impl CsrfToken {
+    pub fn for_session(session_token: &str) -> Self {
+        CsrfToken(hex::encode(Sha256::digest(
+            format!("csrf:{}", session_token).as_bytes(),
+        )))
+    }
+
+    // compare in constant time so the token cannot be guessed byte by byte
+    fn matches(&self, token: &str) -> bool {
+        !self.0.is_empty() && auth::constant_time_eq(&self.0, token)
+    }
+}
+
+impl fmt::Display for CsrfToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// reads do not change anything and need no token
+pub fn is_safe(method: &Method) -> bool {
+    method == Method::GET || method == Method::HEAD || method == Method::OPTIONS
+}
+
+pub fn rejection() -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        "Invalid or missing CSRF token, reload the page and try again",
+    )
+        .into_response()
+}
+
+fn is_form(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"))
+}
+
+// check the token of a request, sent by htmx as a header, by forms as a field
+// multipart forms are sent by htmx so uploads do not have to be read twice
+// returns the request with its body restored if the token is valid, or the response refusing it
+pub async fn verify(expected: &CsrfToken, request: Request) -> Result<Request, Response> {
+    if let Some(token) = request
+        .headers()
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        return if expected.matches(token) {
+            Ok(request)
+        } else {
+            Err(rejection())
+        };
+    }
+    if !is_form(request.headers()) {
+        return Err(rejection());
+    }
+
+    // read by hand, the error of axum::body::to_bytes does not tell a too large body from a broken one
+    let (parts, body) = request.into_parts();
+    let mut bytes = Vec::new();
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let Ok(chunk) = chunk else {
+            return Err((StatusCode::BAD_REQUEST, "The form could not be read").into_response());
+        };
+        if bytes.len() + chunk.len() > FORM_LIMIT {
+            let message = format!("The form is larger than {} MB", FORM_LIMIT / 1024 / 1024);
+            return Err((StatusCode::PAYLOAD_TOO_LARGE, message).into_response());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    // parsed directly, the form extractor would apply its own 2 MB limit to the copy
+    let Ok(form) = serde_urlencoded::from_bytes::<Vec<(String, String)>>(&bytes) else {
+        return Err(rejection());
+    };
+    if form
+        .iter()
+        .any(|(key, value)| key == CSRF_FIELD && expected.matches(value))
+    {
+        Ok(Request::from_parts(parts, Body::from(bytes)))
+    } else {
+        Err(rejection())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token() -> CsrfToken {
+        CsrfToken::for_session("session")
+    }
+
+    fn post(content_type: &str, body: impl Into<Body>) -> Request {
+        Request::post("/projects")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(body.into())
+            .unwrap()
+    }
+
+    fn form(body: String) -> Request {
+        post("application/x-www-form-urlencoded", body)
+    }
+
+    async fn status(request: Request) -> StatusCode {
+        match verify(&token(), request).await {
+            Ok(_) => StatusCode::OK,
+            Err(response) => response.status(),
+        }
+    }
+
+    #[test]
+    fn tokens_are_derived_from_the_session() {
+        assert_eq!(
+            token().to_string(),
+            CsrfToken::for_session("session").to_string()
+        );
+        assert_ne!(
+            token().to_string(),
+            CsrfToken::for_session("other").to_string()
+        );
+        assert_eq!(token().to_string().len(), 64);
+        assert!(token().matches(&token().to_string()));
+    }
+
+    #[test]
+    fn empty_token_matches_nothing() {
+        assert!(!CsrfToken::default().matches(""));
+        assert!(!token().matches(""));
+    }
+
+    #[test]
+    fn only_reads_are_safe() {
+        assert!(is_safe(&Method::GET));
+        assert!(is_safe(&Method::HEAD));
+        assert!(is_safe(&Method::OPTIONS));
+        assert!(!is_safe(&Method::POST));
+        assert!(!is_safe(&Method::PUT));
+        assert!(!is_safe(&Method::DELETE));
+    }
+
+    #[tokio::test]
+    async fn header_token_is_checked() {
+        let mut request = post("application/json", "{}");
+        request
+            .headers_mut()
+            .insert(CSRF_HEADER, token().to_string().parse().unwrap());
+        assert_eq!(status(request).await, StatusCode::OK);
+
+        let mut request = post("application/json", "{}");
+        request
+            .headers_mut()
+            .insert(CSRF_HEADER, "wrong".parse().unwrap());
+        assert_eq!(status(request).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn wrong_header_is_not_rescued_by_the_form() {
+        let mut request = form(format!("{}={}", CSRF_FIELD, token()));
+        request
+            .headers_mut()
+            .insert(CSRF_HEADER, "wrong".parse().unwrap());
+        assert_eq!(status(request).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn form_field_is_checked_and_the_body_kept() {
+        let body = format!("name=Paint+fence&{}={}&notes=a%26b", CSRF_FIELD, token());
+        let request = verify(&token(), form(body.clone())).await.unwrap();
+        let bytes = axum::body::to_bytes(request.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(bytes, body.as_bytes());
+
+        let request = form(format!("name=A&{}=wrong", CSRF_FIELD));
+        assert_eq!(status(request).await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            status(form("name=A".to_string())).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn token_in_the_query_string_is_refused() {
+        let request = Request::post(format!("/projects?{}={}", CSRF_FIELD, token()))
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from("name=A"))
+            .unwrap();
+        assert_eq!(status(request).await, StatusCode::FORBIDDEN);
+
+        let request = Request::delete(format!("/projects/1?{}={}", CSRF_FIELD, token()))
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(status(request).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn other_bodies_need_the_header() {
+        let body = format!("{}={}", CSRF_FIELD, token());
+        assert_eq!(
+            status(post("multipart/form-data; boundary=x", body.clone())).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(post("text/plain", body)).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn large_forms_are_refused() {
+        let body = format!("{}={}&notes=", CSRF_FIELD, token());
+        let padding = "x".repeat(FORM_LIMIT + 1 - body.len());
+        assert_eq!(
+            status(form(format!("{}{}", body, padding))).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        let padding = "x".repeat(FORM_LIMIT - body.len());
+        assert_eq!(
+            status(form(format!("{}{}", body, padding))).await,
+            StatusCode::OK
+        );
+    }
+}
@@ -0,0 +1,773 @@
+use async_trait::async_trait;
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use sqlx::migrate::{Migrate, Migrator};
+use std::sync::Arc;
+
+use crate::models;
+
+mod mysql;
+mod postgres;
+mod sqlite;
+
+// the project was changed (or deleted) after the submitted version was read
+#[derive(Debug)]
+pub struct VersionConflict {
+    pub id: u64,
+}
+
+impl std::fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "project with id {} was changed by someone else", self.id)
+    }
+}
+
+impl std::error::Error for VersionConflict {}
+
+// storage of the tracker, implemented once per supported database
+#[async_trait]
+pub trait Repository: Send + Sync {
+    // apply the migrations embedded in the binary
+    async fn migrate(&self) -> anyhow::Result<()>;
+
+    // get all projects of a user
+    async fn get_projects(&self, owner_id: u64) -> anyhow::Result<Vec<models::Project>>;
+
+    // get projects in a category of a user
+    async fn get_category_projects(
+        &self,
+        owner_id: u64,
+        category: &str,
+    ) -> anyhow::Result<Vec<models::Project>>;
+
+    // get project with id
+    async fn get_project(&self, id: u64) -> anyhow::Result<Option<models::Project>>;
+
+    // get most recent activity events of a user
+    async fn get_recent_activity(
+        &self,
+        owner_id: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<models::Activity>>;
+
+    // start project
+    async fn start_project(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()>;
+
+    // complete project
+    async fn complete_project(&self, id: u64, version: u64) -> anyhow::Result<()>;
+
+    // get completed (and optionally in progress) projects overlapping a date range,
+    // limited to the given categories unless the list is empty
+    async fn get_timeline_projects(
+        &self,
+        owner_id: u64,
+        start: NaiveDate,
+        end: NaiveDate,
+        categories: &[String],
+        in_progress: bool,
+    ) -> anyhow::Result<Vec<models::Project>>;
+
+    // get block of completed projects
+    async fn get_completed_projects(
+        &self,
+        owner_id: u64,
+        block: u64,
+        page_size: u64,
+    ) -> anyhow::Result<models::CompletedPage>;
+
+    // get projects completed in a year
+    async fn get_completed_projects_by_year(
+        &self,
+        owner_id: u64,
+        year: i32,
+    ) -> anyhow::Result<Vec<models::Project>>;
+
+    // get number of completed projects per month and category since a date
+    async fn get_completed_per_month(
+        &self,
+        owner_id: u64,
+        since: NaiveDate,
+    ) -> anyhow::Result<Vec<models::CompletedPerMonth>>;
+
+    // get median and 90th percentile of cycle time (start to complete) and lead time (create to complete) in days
+    async fn get_duration_stats(&self, owner_id: u64) -> anyhow::Result<models::DurationStats>;
+
+    // get number of in progress projects per category
+    async fn get_work_in_progress(
+        &self,
+        owner_id: u64,
+    ) -> anyhow::Result<Vec<models::CategoryCount>>;
+
+    // get number of backlog projects per age bucket, cutoffs are creation dates from newest to oldest
+    async fn get_backlog_age(
+        &self,
+        owner_id: u64,
+        cutoffs: [NaiveDate; 4],
+    ) -> anyhow::Result<Vec<models::AgeBucket>>;
+
+    // get the oldest projects that have not been started
+    async fn get_oldest_backlog(
+        &self,
+        owner_id: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<models::Project>>;
+
+    // get highest position in a category of a user
+    async fn get_highest_position_by_category(
+        &self,
+        owner_id: u64,
+        category: &str,
+    ) -> anyhow::Result<Option<u64>>;
+
+    // add project
+    async fn add_project(
+        &self,
+        owner_id: u64,
+        name: String,
+        category: String,
+    ) -> anyhow::Result<u64>;
+
+    // add imported projects at the end of their categories, all of them or none
+    async fn import_projects(
+        &self,
+        owner_id: u64,
+        projects: &[models::NewProject],
+    ) -> anyhow::Result<()>;
+
+    // delete project
+    async fn delete_project(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()>;
+
+    // move project up
+    async fn move_project_up(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()>;
+
+    // move project down
+    async fn move_project_down(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()>;
+
+    // update notes
+    async fn update_notes(&self, id: u64, notes: String, version: u64) -> anyhow::Result<()>;
+
+    // get all webhooks of a user
+    async fn get_webhooks(&self, owner_id: u64) -> anyhow::Result<Vec<models::Webhook>>;
+
+    // add webhook, events is a comma separated list of event names (empty for all events)
+    async fn add_webhook(
+        &self,
+        owner_id: u64,
+        url: &str,
+        secret: &str,
+        events: &str,
+    ) -> anyhow::Result<u64>;
+
+    // delete webhook
+    async fn delete_webhook(&self, owner_id: u64, id: u64) -> anyhow::Result<()>;
+
+    // log a webhook delivery attempt
+    async fn add_webhook_delivery(
+        &self,
+        webhook_id: u64,
+        event: &str,
+        attempt: u64,
+        status_code: Option<u64>,
+        error: &str,
+    ) -> anyhow::Result<()>;
+
+    // get most recent deliveries to the webhooks of a user
+    async fn get_webhook_deliveries(
+        &self,
+        owner_id: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<models::WebhookDelivery>>;
+
+    // count user accounts
+    async fn count_users(&self) -> anyhow::Result<i64>;
+
+    // get user with username
+    async fn get_user_by_username(&self, username: &str) -> anyhow::Result<Option<models::User>>;
+
+    // get the names of all users
+    async fn get_users(&self) -> anyhow::Result<Vec<models::UserName>>;
+
+    // add user with an already hashed password and its first categories,
+    // data from before accounts existed (owner 0) is handed to the new user
+    async fn add_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+        categories: &[String],
+    ) -> anyhow::Result<u64>;
+
+    // add the first user like add_user, None if there already is a user
+    async fn add_first_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+        categories: &[String],
+    ) -> anyhow::Result<Option<u64>>;
+
+    // replace the password hash of a user
+    async fn update_password(&self, id: u64, password_hash: &str) -> anyhow::Result<()>;
+
+    // add session, only the hash of the session token is stored
+    async fn add_session(
+        &self,
+        token_hash: &str,
+        user_id: u64,
+        expires_at: NaiveDateTime,
+    ) -> anyhow::Result<()>;
+
+    // get the user of an unexpired session
+    async fn get_session_user(&self, token_hash: &str) -> anyhow::Result<Option<models::User>>;
+
+    // delete session
+    async fn delete_session(&self, token_hash: &str) -> anyhow::Result<()>;
+
+    // get categories of a user
+    async fn get_categories(&self, owner_id: u64) -> anyhow::Result<Vec<models::Category>>;
+
+    // get categories other users share with a user
+    async fn get_shared_categories(&self, user_id: u64) -> anyhow::Result<Vec<models::Category>>;
+
+    // get category with id
+    async fn get_category(&self, id: u64) -> anyhow::Result<Option<models::Category>>;
+
+    // get category of a user by name
+    async fn get_category_by_name(
+        &self,
+        owner_id: u64,
+        name: &str,
+    ) -> anyhow::Result<Option<models::Category>>;
+
+    // add category, existing categories are left alone
+    async fn add_category(&self, owner_id: u64, name: &str) -> anyhow::Result<()>;
+
+    // delete category of a user, only categories without projects can be deleted
+    async fn delete_category(&self, owner_id: u64, id: u64) -> anyhow::Result<bool>;
+
+    // get shares of the categories of a user
+    async fn get_category_shares(
+        &self,
+        owner_id: u64,
+    ) -> anyhow::Result<Vec<models::CategoryShare>>;
+
+    // share a category with a user, sharing again changes the access
+    async fn share_category(
+        &self,
+        category_id: u64,
+        user_id: u64,
+        access: models::Access,
+    ) -> anyhow::Result<()>;
+
+    // stop sharing, only the owner of the category can remove its shares
+    async fn delete_share(&self, owner_id: u64, id: u64) -> anyhow::Result<()>;
+
+    // access of a user to the projects in a category, owners have full access
+    async fn get_access(
+        &self,
+        user_id: u64,
+        owner_id: u64,
+        category: &str,
+    ) -> anyhow::Result<Option<models::Access>>;
+
+    // restore the projects of a user from a backup file, restored projects get new ids,
+    // their categories have to exist
+    async fn restore_projects(
+        &self,
+        owner_id: u64,
+        projects: Vec<models::Project>,
+    ) -> anyhow::Result<()>;
+
+    // get user with id
+    async fn get_user(&self, id: u64) -> anyhow::Result<Option<models::User>>;
+
+    // hash of the calendar feed token of a user, none until one is created
+    async fn get_calendar_token_hash(&self, user_id: u64) -> anyhow::Result<Option<String>>;
+
+    // replace the calendar feed token of a user, the previous feed url stops working
+    async fn set_calendar_token_hash(&self, user_id: u64, token_hash: &str) -> anyhow::Result<()>;
+
+    // get api tokens of a user
+    async fn get_api_tokens(&self, user_id: u64) -> anyhow::Result<Vec<models::ApiToken>>;
+
+    // add api token, only the hash of the token is stored
+    async fn add_api_token(
+        &self,
+        user_id: u64,
+        name: &str,
+        token_hash: &str,
+        scopes: &str,
+    ) -> anyhow::Result<()>;
+
+    // revoke api token of a user
+    async fn delete_api_token(&self, user_id: u64, id: u64) -> anyhow::Result<()>;
+
+    // get api token by the hash of the token and mark it as used
+    async fn use_api_token(&self, token_hash: &str) -> anyhow::Result<Option<models::ApiToken>>;
+}
+
+// shared handle to the repository, cheap to clone into the app state and background tasks
+pub type Db = Arc<dyn Repository>;
+
+// connect to the database of a connection url, the scheme selects the implementation
+pub async fn connect(url: &str) -> anyhow::Result<Db> {
+    let db: Db = if url.starts_with("sqlite:") {
+        Arc::new(sqlite::SqliteRepository::connect(url).await?)
+    } else if url.starts_with("mysql:") {
+        Arc::new(mysql::MySqlRepository::connect(url).await?)
+    } else if url.starts_with("postgres:") || url.starts_with("postgresql:") {
+        Arc::new(postgres::PostgresRepository::connect(url).await?)
+    } else {
+        anyhow::bail!("Unsupported database url, expected a mysql:, postgres: or sqlite: url");
+    };
+    Ok(db)
+}
+
+// apply pending migrations, unless the database was migrated by a newer version of the tracker
+async fn migrate<DB>(migrator: &Migrator, pool: &sqlx::Pool<DB>) -> anyhow::Result<()>
+where
+    DB: sqlx::Database,
+    DB::Connection: Migrate,
+{
+    let mut connection = pool.acquire().await?;
+    connection.ensure_migrations_table().await?;
+    let applied = connection.list_applied_migrations().await?;
+    drop(connection);
+
+    let latest = migrator
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or(0);
+    if let Some(unknown) = applied
+        .iter()
+        .find(|migration| !migrator.version_exists(migration.version))
+    {
+        anyhow::bail!(
+            "Database schema version {} is newer than this binary (latest migration {}), upgrade the tracker",
+            unknown.version,
+            latest
+        );
+    }
+
+    migrator.run(pool).await?;
+    println!("{} - Database - schema at version {}", Local::now(), latest);
+
+    Ok(())
+}
+
+// url of a new sqlite database file, for tests
+#[cfg(test)]
+fn test_db_url() -> String {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    let file = std::env::temp_dir().join(format!(
+        "project-tracker-test-{}-{}.db",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_file(&file);
+    format!("sqlite://{}", file.display())
+}
+
+// empty, migrated sqlite database in its own file, for tests
+#[cfg(test)]
+pub async fn test_db() -> Db {
+    let db = connect(&test_db_url()).await.unwrap();
+    db.migrate().await.unwrap();
+    db
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqliteConnectOptions;
+    use sqlx::SqlitePool;
+    use std::str::FromStr;
+
+    static MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite");
+
+    async fn pool(url: &str) -> SqlitePool {
+        let options = SqliteConnectOptions::from_str(url)
+            .unwrap()
+            .create_if_missing(true);
+        SqlitePool::connect_with(options).await.unwrap()
+    }
+
+    // the migrations are reversible, each version also has a down migration
+    fn up_migrations() -> impl Iterator<Item = &'static sqlx::migrate::Migration> {
+        MIGRATOR
+            .iter()
+            .filter(|migration| migration.migration_type.is_up_migration())
+    }
+
+    async fn applied(pool: &SqlitePool) -> Vec<i64> {
+        let mut connection = pool.acquire().await.unwrap();
+        let mut versions: Vec<_> = connection
+            .list_applied_migrations()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|migration| migration.version)
+            .collect();
+        versions.sort();
+        versions
+    }
+
+    #[tokio::test]
+    async fn migrations_apply_to_an_empty_database() {
+        let pool = pool(&test_db_url()).await;
+        migrate(&MIGRATOR, &pool).await.unwrap();
+        let versions: Vec<_> = up_migrations().map(|migration| migration.version).collect();
+        assert_eq!(applied(&pool).await, versions);
+    }
+
+    #[tokio::test]
+    async fn migrating_again_keeps_the_data() {
+        let db = test_db().await;
+        let user_id = db.add_user("alice", "hash", &[]).await.unwrap();
+        db.add_category(user_id, "Work").await.unwrap();
+        db.migrate().await.unwrap();
+        db.migrate().await.unwrap();
+        let categories = db.get_categories(user_id).await.unwrap();
+        assert_eq!(categories.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn newer_schema_is_refused() {
+        let pool = pool(&test_db_url()).await;
+        migrate(&MIGRATOR, &pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+             VALUES (99990101000000, 'from the future', TRUE, X'00', 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let error = migrate(&MIGRATOR, &pool).await.unwrap_err().to_string();
+        assert!(
+            error.starts_with("Database schema version 99990101000000 is newer than this binary"),
+            "{}",
+            error
+        );
+        // nothing is changed
+        assert_eq!(applied(&pool).await.last(), Some(&99990101000000));
+    }
+
+    #[tokio::test]
+    async fn projects_of_the_first_schema_are_upgraded() {
+        let url = test_db_url();
+        let pool = pool(&url).await;
+        let mut connection = pool.acquire().await.unwrap();
+        connection.ensure_migrations_table().await.unwrap();
+        connection
+            .apply(up_migrations().next().unwrap())
+            .await
+            .unwrap();
+        drop(connection);
+        sqlx::query(
+            "INSERT INTO projects (name, category, position, status, creation_date) \
+             VALUES ('Old', 'Work', 1, 1, '2024-03-06')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let db = connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+        let user_id = db
+            .add_first_user("alice", "hash", &[])
+            .await
+            .unwrap()
+            .unwrap();
+
+        let projects = db.get_projects(user_id).await.unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "Old");
+        assert_eq!(projects[0].status, 1);
+        assert_eq!(projects[0].version, 1);
+        assert_eq!(
+            projects[0].creation_date,
+            NaiveDate::from_ymd_opt(2024, 3, 6).unwrap()
+        );
+        let categories = db.get_categories(user_id).await.unwrap();
+        assert_eq!(categories[0].name, "Work");
+    }
+
+    #[tokio::test]
+    async fn timeline_categories_may_contain_commas() {
+        let db = test_db().await;
+        let user_id = db.add_user("alice", "hash", &[]).await.unwrap();
+        let date = |day| NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+        let project = |name: &str, category: &str| models::NewProject {
+            name: name.to_string(),
+            category: category.to_string(),
+            status: 2,
+            notes: String::new(),
+            creation_date: date(1),
+            start_date: date(2),
+            completion_date: date(5),
+        };
+        db.import_projects(
+            user_id,
+            &[
+                project("Beds", "Home, Garden"),
+                project("Hedge", "Home"),
+                project("Report", "Work"),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let names = |projects: Vec<models::Project>| {
+            let mut names: Vec<_> = projects.into_iter().map(|project| project.name).collect();
+            names.sort();
+            names
+        };
+        let categories = ["Home, Garden".to_string()];
+        let projects = db
+            .get_timeline_projects(user_id, date(1), date(31), &categories, false)
+            .await
+            .unwrap();
+        assert_eq!(names(projects), ["Beds"]);
+
+        let categories = ["Home, Garden".to_string(), "Work".to_string()];
+        let projects = db
+            .get_timeline_projects(user_id, date(1), date(31), &categories, false)
+            .await
+            .unwrap();
+        assert_eq!(names(projects), ["Beds", "Report"]);
+
+        let projects = db
+            .get_timeline_projects(user_id, date(1), date(31), &[], false)
+            .await
+            .unwrap();
+        assert_eq!(names(projects), ["Beds", "Hedge", "Report"]);
+    }
+
+    #[tokio::test]
+    async fn position_shifts_do_not_conflict_with_notes() {
+        let db = test_db().await;
+        let user_id = db.add_user("alice", "hash", &[]).await.unwrap();
+        let mut ids = Vec::new();
+        for name in ["Report", "Slides", "Budget"] {
+            let id = db
+                .add_project(user_id, name.to_string(), "Work".to_string())
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        let project = |id| {
+            let db = &db;
+            async move { db.get_project(id).await.unwrap().unwrap() }
+        };
+        let is_conflict = |result: anyhow::Result<()>| result.unwrap_err().is::<VersionConflict>();
+
+        // a page rendered before the shifts still holds the current versions
+        let slides = project(ids[1]).await;
+        let budget = project(ids[2]).await;
+        db.move_project_up(budget.id, user_id, "Work".to_string(), 3, budget.version)
+            .await
+            .unwrap();
+        let report = project(ids[0]).await;
+        db.delete_project(report.id, user_id, "Work".to_string(), 1, report.version)
+            .await
+            .unwrap();
+        assert_eq!(project(ids[1]).await.position, 2);
+        assert_eq!(project(ids[2]).await.position, 1);
+
+        db.update_notes(slides.id, "draft".to_string(), slides.version)
+            .await
+            .unwrap();
+        db.update_notes(budget.id, "numbers".to_string(), budget.version)
+            .await
+            .unwrap();
+
+        // the notes write does bump the version, a second write from the same page conflicts
+        assert!(is_conflict(
+            db.update_notes(slides.id, "other".to_string(), slides.version)
+                .await
+        ));
+        assert_eq!(project(ids[1]).await.notes, "draft");
+        assert!(is_conflict(
+            db.complete_project(slides.id, slides.version).await
+        ));
+        let slides = project(ids[1]).await;
+        db.complete_project(slides.id, slides.version)
+            .await
+            .unwrap();
+        assert_eq!(project(ids[1]).await.status, 2);
+    }
+
+    #[tokio::test]
+    async fn access_follows_ownership_and_shares() {
+        let db = test_db().await;
+        let categories = ["Work".to_string(), "Home".to_string()];
+        let alice = db.add_user("alice", "hash", &categories).await.unwrap();
+        let bob = db.add_user("bob", "hash", &categories).await.unwrap();
+        let access = |user_id, owner_id, category| {
+            let db = &db;
+            async move { db.get_access(user_id, owner_id, category).await.unwrap() }
+        };
+
+        assert_eq!(
+            access(alice, alice, "Work").await,
+            Some(models::Access::Edit)
+        );
+        assert_eq!(access(bob, alice, "Work").await, None);
+        assert!(db.get_shared_categories(bob).await.unwrap().is_empty());
+
+        let work = db
+            .get_category_by_name(alice, "Work")
+            .await
+            .unwrap()
+            .unwrap();
+        db.share_category(work.id, bob, models::Access::Read)
+            .await
+            .unwrap();
+        assert_eq!(access(bob, alice, "Work").await, Some(models::Access::Read));
+        assert_eq!(access(bob, alice, "Home").await, None);
+        // shares go one way, the category of bob with the same name stays his own
+        assert_eq!(access(alice, bob, "Work").await, None);
+        let shared = db.get_shared_categories(bob).await.unwrap();
+        assert_eq!(shared.len(), 1);
+        assert_eq!((shared[0].id, shared[0].owner_id), (work.id, alice));
+        assert!(db.get_shared_categories(alice).await.unwrap().is_empty());
+
+        // sharing again changes the access instead of adding a second share
+        db.share_category(work.id, bob, models::Access::Edit)
+            .await
+            .unwrap();
+        assert_eq!(access(bob, alice, "Work").await, Some(models::Access::Edit));
+        let shares = db.get_category_shares(alice).await.unwrap();
+        assert_eq!(shares.len(), 1);
+
+        // only the owner can stop sharing
+        db.delete_share(bob, shares[0].id).await.unwrap();
+        assert_eq!(access(bob, alice, "Work").await, Some(models::Access::Edit));
+        db.delete_share(alice, shares[0].id).await.unwrap();
+        assert_eq!(access(bob, alice, "Work").await, None);
+        assert!(db.get_shared_categories(bob).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn completed_projects_are_paged() {
+        let db = test_db().await;
+        let alice = db.add_user("alice", "hash", &[]).await.unwrap();
+        let bob = db.add_user("bob", "hash", &[]).await.unwrap();
+        let project = |name: &str, status, day| models::NewProject {
+            name: name.to_string(),
+            category: "Work".to_string(),
+            status,
+            notes: String::new(),
+            creation_date: NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            start_date: NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            completion_date: NaiveDate::from_ymd_opt(2026, 3, day).unwrap(),
+        };
+        db.import_projects(
+            alice,
+            &[
+                project("Report", 2, 2),
+                project("Slides", 2, 4),
+                project("Budget", 2, 3),
+                project("Review", 2, 5),
+                project("Backlog", 0, 1),
+            ],
+        )
+        .await
+        .unwrap();
+        db.import_projects(bob, &[project("Other", 2, 6)])
+            .await
+            .unwrap();
+        let page = |block, page_size| {
+            let db = &db;
+            async move {
+                let page = db
+                    .get_completed_projects(alice, block, page_size)
+                    .await
+                    .unwrap();
+                let names: Vec<_> = page.projects.into_iter().map(|p| p.name).collect();
+                (names, page.total, page.has_more)
+            }
+        };
+
+        // newest first, only the completed projects of the user are counted
+        assert_eq!(
+            page(1, 3).await,
+            (
+                vec!["Review".into(), "Slides".into(), "Budget".into()],
+                4,
+                true
+            )
+        );
+        assert_eq!(page(2, 3).await, (vec!["Report".into()], 4, false));
+        // a full last page has nothing after it
+        assert_eq!(
+            page(2, 2).await,
+            (vec!["Budget".into(), "Report".into()], 4, false)
+        );
+        assert!(!page(1, 4).await.2);
+        assert_eq!(page(3, 2).await, (Vec::new(), 4, false));
+    }
+
+    #[tokio::test]
+    async fn year_in_review_includes_the_first_and_last_day() {
+        let db = test_db().await;
+        let user_id = db.add_user("alice", "hash", &[]).await.unwrap();
+        let project = |name: &str, completion: NaiveDate| models::NewProject {
+            name: name.to_string(),
+            category: "Work".to_string(),
+            status: 2,
+            notes: String::new(),
+            creation_date: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
+            start_date: NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
+            completion_date: completion,
+        };
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        db.import_projects(
+            user_id,
+            &[
+                project("Old", date(2025, 12, 31)),
+                project("First", date(2026, 1, 1)),
+                project("Last", date(2026, 12, 31)),
+                project("New", date(2027, 1, 1)),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let projects = db
+            .get_completed_projects_by_year(user_id, 2026)
+            .await
+            .unwrap();
+        let names: Vec<_> = projects.into_iter().map(|project| project.name).collect();
+        assert_eq!(names, ["First", "Last"]);
+    }
+}
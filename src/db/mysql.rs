@@ -0,0 +1,1700 @@
+use async_trait::async_trait;
+use chrono::{Local, NaiveDate, NaiveDateTime, Utc};
+use sqlx::migrate::{MigrateDatabase, Migrator};
+use sqlx::mysql::{MySql, MySqlPool};
+
+use super::{Repository, VersionConflict};
+use crate::models;
+
+static MIGRATOR: Migrator = sqlx::migrate!("migrations/mysql");
+
+// MariaDB or MySQL database, queries are checked at compile time against the offline query cache
+pub struct MySqlRepository {
+    pool: MySqlPool,
+}
+
+impl MySqlRepository {
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        // the database itself has to exist before migrations can create its tables
+        if !MySql::database_exists(url).await? {
+            MySql::create_database(url).await?;
+        }
+        let pool = MySqlPool::connect(url).await?;
+        Ok(MySqlRepository { pool })
+    }
+}
+
+// record activity event for a project, must run before the project is deleted
+async fn record_activity<'e, E>(executor: E, id: u64, event: &str) -> anyhow::Result<()>
+where
+    E: sqlx::Executor<'e, Database = MySql>,
+{
+    let created_at = Utc::now().naive_utc();
+    sqlx::query!(
+        r#"
+            INSERT INTO activity ( project_id, owner_id, project_name, category, event, created_at )
+            SELECT id, owner_id, name, category, ?, ?
+            FROM projects
+            WHERE id = ?
+        "#,
+        event,
+        created_at,
+        id
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+// position after the last project of a category, new and imported projects are added at the end
+async fn next_position<'e, E>(executor: E, owner_id: u64, category: &str) -> anyhow::Result<u64>
+where
+    E: sqlx::Executor<'e, Database = MySql>,
+{
+    let highest_position = sqlx::query!(
+        r#"
+            SELECT MAX(position) AS max_position
+            FROM projects
+            WHERE owner_id = ? AND category = ?
+        "#,
+        owner_id,
+        category
+    )
+    .fetch_one(executor)
+    .await?
+    .max_position;
+
+    Ok(highest_position.map_or(1, |position| position + 1))
+}
+
+// add category, existing categories are left alone
+async fn insert_category<'e, E>(executor: E, owner_id: u64, name: &str) -> anyhow::Result<()>
+where
+    E: sqlx::Executor<'e, Database = MySql>,
+{
+    sqlx::query!(
+        r#"
+            INSERT IGNORE INTO categories ( owner_id, name )
+            VALUES ( ?, ? )
+        "#,
+        owner_id,
+        name
+    )
+    .execute(executor)
+    .await?;
+
+    println!(
+        "{} - Database - added category {} for user with id {}",
+        Local::now(),
+        name,
+        owner_id
+    );
+
+    Ok(())
+}
+
+// first categories of a new user, data from before accounts existed (owner 0) is handed to it
+async fn setup_user(
+    transaction: &mut sqlx::Transaction<'_, MySql>,
+    user_id: u64,
+    categories: &[String],
+) -> anyhow::Result<()> {
+    for category in categories {
+        insert_category(&mut **transaction, user_id, category).await?;
+    }
+
+    sqlx::query!(
+        r#"
+            UPDATE projects SET owner_id = ?
+            WHERE owner_id = 0
+        "#,
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+            UPDATE activity SET owner_id = ?
+            WHERE owner_id = 0
+        "#,
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+            UPDATE webhooks SET owner_id = ?
+            WHERE owner_id = 0
+        "#,
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+            INSERT IGNORE INTO categories ( owner_id, name )
+            SELECT DISTINCT owner_id, category FROM projects
+            WHERE owner_id = ?
+        "#,
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl Repository for MySqlRepository {
+    async fn migrate(&self) -> anyhow::Result<()> {
+        super::migrate(&MIGRATOR, &self.pool).await
+    }
+
+    async fn get_projects(&self, owner_id: u64) -> anyhow::Result<Vec<models::Project>> {
+        let pool = &self.pool;
+        let projects = sqlx::query_as!(
+            models::Project,
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ?
+                ORDER BY position
+            "#,
+            owner_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} projects",
+            Local::now(),
+            projects.len()
+        );
+        Ok(projects)
+    }
+
+    async fn get_category_projects(
+        &self,
+        owner_id: u64,
+        category: &str,
+    ) -> anyhow::Result<Vec<models::Project>> {
+        let pool = &self.pool;
+        let projects = sqlx::query_as!(
+            models::Project,
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ? AND category = ?
+                ORDER BY position
+            "#,
+            owner_id,
+            category
+        )
+        .fetch_all(pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} projects in {}",
+            Local::now(),
+            projects.len(),
+            category
+        );
+        Ok(projects)
+    }
+
+    async fn get_project(&self, id: u64) -> anyhow::Result<Option<models::Project>> {
+        let pool = &self.pool;
+        let project = sqlx::query_as!(
+            models::Project,
+            r#"
+                SELECT * FROM projects
+                WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched project with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(project)
+    }
+
+    async fn get_recent_activity(
+        &self,
+        owner_id: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<models::Activity>> {
+        let pool = &self.pool;
+        let activity = sqlx::query_as!(
+            models::Activity,
+            r#"
+                SELECT id, project_id, project_name, category, event, created_at FROM activity
+                WHERE owner_id = ?
+                ORDER BY created_at DESC, id DESC
+                LIMIT ?
+            "#,
+            owner_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} activity events",
+            Local::now(),
+            activity.len()
+        );
+        Ok(activity)
+    }
+
+    async fn start_project(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let mut transaction = pool.begin().await?;
+
+        let current_date = Local::now();
+        // update the status of the started project to 1 and move to position 1
+        let updated = sqlx::query!(
+            r#"
+                UPDATE projects 
+                SET status = 1, position = 1, start_date = ?, version = version + 1
+                WHERE id = ? AND version = ?
+            "#,
+            current_date,
+            id,
+            version
+        )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        // move all projects before started project position back
+        sqlx::query!(
+            r#"
+                UPDATE projects SET position = position + 1
+                WHERE owner_id = ? AND category = ?
+                AND position <= ?
+                AND id != ?
+            "#,
+            owner_id,
+            category,
+            position,
+            id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        record_activity(&mut *transaction, id, "started").await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - started project with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn complete_project(&self, id: u64, version: u64) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let current_date = Local::now();
+        let updated = sqlx::query!(
+            r#"
+                UPDATE projects SET status = 2, completion_date = ?, version = version + 1
+                WHERE id = ? AND version = ?
+            "#,
+            current_date,
+            id,
+            version
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        record_activity(pool, id, "completed").await?;
+
+        print!(
+            "{} - Database - completed project with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn get_timeline_projects(
+        &self,
+        owner_id: u64,
+        start: NaiveDate,
+        end: NaiveDate,
+        categories: &[String],
+        in_progress: bool,
+    ) -> anyhow::Result<Vec<models::Project>> {
+        let pool = &self.pool;
+        // the list is bound as one json array, so the query keeps a fixed number of parameters
+        let category_list = serde_json::to_string(categories)?;
+        let projects = sqlx::query_as!(
+            models::Project,
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ?
+                AND ( status = 2 OR ( ? AND status = 1 ) )
+                AND start_date <= ?
+                AND ( status = 1 OR completion_date >= ? )
+                AND ( ? OR JSON_CONTAINS(?, JSON_QUOTE(category)) )
+                ORDER BY start_date, id
+            "#,
+            owner_id,
+            in_progress,
+            end,
+            start,
+            categories.is_empty(),
+            category_list
+        )
+        .fetch_all(pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} timeline projects between {} and {}",
+            Local::now(),
+            projects.len(),
+            start,
+            end
+        );
+        Ok(projects)
+    }
+
+    async fn get_completed_projects(
+        &self,
+        owner_id: u64,
+        block: u64,
+        page_size: u64,
+    ) -> anyhow::Result<models::CompletedPage> {
+        let pool = &self.pool;
+        let offset = block.saturating_sub(1) * page_size;
+
+        let projects = sqlx::query_as!(
+            models::Project,
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ? AND status = 2
+                ORDER BY completion_date DESC, id DESC
+                LIMIT ? OFFSET ?
+            "#,
+            owner_id,
+            page_size,
+            offset
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let total = sqlx::query!(
+            r#"
+                SELECT COUNT(*) AS total
+                FROM projects
+                WHERE owner_id = ? AND status = 2
+            "#,
+            owner_id
+        )
+        .fetch_one(pool)
+        .await?
+        .total as u64;
+
+        println!(
+            "{} - Database - fetched block {} of completed projects",
+            Local::now(),
+            block
+        );
+
+        Ok(models::CompletedPage {
+            has_more: offset + (projects.len() as u64) < total,
+            projects,
+            total,
+        })
+    }
+
+    async fn get_completed_projects_by_year(
+        &self,
+        owner_id: u64,
+        year: i32,
+    ) -> anyhow::Result<Vec<models::Project>> {
+        let pool = &self.pool;
+        let projects = sqlx::query_as!(
+            models::Project,
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ? AND status = 2
+                AND YEAR(completion_date) = ?
+                ORDER BY completion_date, id
+            "#,
+            owner_id,
+            year
+        )
+        .fetch_all(pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} projects completed in {}",
+            Local::now(),
+            projects.len(),
+            year
+        );
+        Ok(projects)
+    }
+
+    async fn get_completed_per_month(
+        &self,
+        owner_id: u64,
+        since: NaiveDate,
+    ) -> anyhow::Result<Vec<models::CompletedPerMonth>> {
+        let pool = &self.pool;
+        let completed = sqlx::query_as!(
+            models::CompletedPerMonth,
+            r#"
+                SELECT DATE_FORMAT(completion_date, '%Y-%m') AS month, category, COUNT(*) AS completed
+                FROM projects
+                WHERE owner_id = ? AND status = 2
+                AND completion_date >= ?
+                GROUP BY month, category
+                ORDER BY month DESC, category
+            "#,
+            owner_id,
+            since
+        )
+        .fetch_all(pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched completed projects per month since {}",
+            Local::now(),
+            since
+        );
+        Ok(completed)
+    }
+
+    async fn get_duration_stats(&self, owner_id: u64) -> anyhow::Result<models::DurationStats> {
+        let pool = &self.pool;
+        // nearest rank percentiles: smallest duration with a cumulative distribution of at least p
+        let stats = sqlx::query_as!(
+            models::DurationStats,
+            r#"
+                SELECT
+                    MIN(CASE WHEN cycle_rank >= 0.5 THEN cycle_days END) AS cycle_median,
+                    MIN(CASE WHEN cycle_rank >= 0.9 THEN cycle_days END) AS cycle_p90,
+                    MIN(CASE WHEN lead_rank >= 0.5 THEN lead_days END) AS lead_median,
+                    MIN(CASE WHEN lead_rank >= 0.9 THEN lead_days END) AS lead_p90
+                FROM (
+                    SELECT
+                        DATEDIFF(completion_date, start_date) AS cycle_days,
+                        DATEDIFF(completion_date, creation_date) AS lead_days,
+                        CUME_DIST() OVER (ORDER BY DATEDIFF(completion_date, start_date)) AS cycle_rank,
+                        CUME_DIST() OVER (ORDER BY DATEDIFF(completion_date, creation_date)) AS lead_rank
+                    FROM projects
+                    WHERE owner_id = ? AND status = 2
+                ) AS durations
+            "#,
+            owner_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        println!("{} - Database - fetched duration statistics", Local::now());
+        Ok(stats)
+    }
+
+    async fn get_work_in_progress(
+        &self,
+        owner_id: u64,
+    ) -> anyhow::Result<Vec<models::CategoryCount>> {
+        let pool = &self.pool;
+        let wip = sqlx::query_as!(
+            models::CategoryCount,
+            r#"
+                SELECT category, COUNT(*) AS total
+                FROM projects
+                WHERE owner_id = ? AND status = 1
+                GROUP BY category
+                ORDER BY category
+            "#,
+            owner_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        println!("{} - Database - fetched work in progress", Local::now());
+        Ok(wip)
+    }
+
+    async fn get_backlog_age(
+        &self,
+        owner_id: u64,
+        cutoffs: [NaiveDate; 4],
+    ) -> anyhow::Result<Vec<models::AgeBucket>> {
+        let pool = &self.pool;
+        let buckets = sqlx::query_as!(
+            models::AgeBucket,
+            r#"
+                SELECT
+                    CASE
+                        WHEN creation_date > ? THEN 0
+                        WHEN creation_date > ? THEN 1
+                        WHEN creation_date > ? THEN 2
+                        WHEN creation_date > ? THEN 3
+                        ELSE 4
+                    END AS bucket,
+                    COUNT(*) AS total
+                FROM projects
+                WHERE owner_id = ? AND status = 0
+                GROUP BY bucket
+                ORDER BY bucket
+            "#,
+            cutoffs[0],
+            cutoffs[1],
+            cutoffs[2],
+            cutoffs[3],
+            owner_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched backlog age distribution",
+            Local::now()
+        );
+        Ok(buckets)
+    }
+
+    async fn get_oldest_backlog(
+        &self,
+        owner_id: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<models::Project>> {
+        let pool = &self.pool;
+        let projects = sqlx::query_as!(
+            models::Project,
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ? AND status = 0
+                ORDER BY creation_date, id
+                LIMIT ?
+            "#,
+            owner_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} oldest backlog projects",
+            Local::now(),
+            projects.len()
+        );
+        Ok(projects)
+    }
+
+    async fn get_highest_position_by_category(
+        &self,
+        owner_id: u64,
+        category: &str,
+    ) -> anyhow::Result<Option<u64>> {
+        let pool = &self.pool;
+        let result = sqlx::query!(
+            r#"
+                SELECT MAX(position) AS max_position
+                FROM projects
+                WHERE owner_id = ? AND category = ?
+            "#,
+            owner_id,
+            category
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result.map(|row| row.max_position.unwrap_or(0)))
+    }
+
+    async fn add_project(
+        &self,
+        owner_id: u64,
+        name: String,
+        category: String,
+    ) -> anyhow::Result<u64> {
+        let pool = &self.pool;
+        let position = next_position(pool, owner_id, &category).await?;
+
+        let project_id = sqlx::query!(
+            r#"
+                INSERT INTO projects ( owner_id, name, category, position )
+                VALUES ( ?, ?, ?, ? )
+            "#,
+            owner_id,
+            name,
+            category,
+            position
+        )
+        .execute(pool)
+        .await?
+        .last_insert_id();
+
+        record_activity(pool, project_id, "added").await?;
+
+        println!(
+            "{} - Database - added {} to {} at position {} with id {}",
+            Local::now(),
+            name,
+            category,
+            position,
+            project_id
+        );
+
+        Ok(project_id)
+    }
+
+    async fn import_projects(
+        &self,
+        owner_id: u64,
+        projects: &[models::NewProject],
+    ) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let mut transaction = pool.begin().await?;
+
+        for project in projects {
+            let position = next_position(&mut *transaction, owner_id, &project.category).await?;
+
+            let project_id = sqlx::query!(
+                r#"
+                    INSERT INTO projects ( owner_id, name, category, position, status, notes, creation_date, start_date, completion_date )
+                    VALUES ( ?, ?, ?, ?, ?, ?, ?, ?, ? )
+                "#,
+                owner_id,
+                project.name,
+                project.category,
+                position,
+                project.status,
+                project.notes,
+                project.creation_date,
+                project.start_date,
+                project.completion_date
+            )
+            .execute(&mut *transaction)
+            .await?
+            .last_insert_id();
+
+            record_activity(&mut *transaction, project_id, "imported").await?;
+
+            println!(
+                "{} - Database - imported {} to {} at position {} with id {}",
+                Local::now(),
+                project.name,
+                project.category,
+                position,
+                project_id
+            );
+        }
+
+        transaction.commit().await?;
+        println!(
+            "{} - Database - imported {} projects",
+            Local::now(),
+            projects.len()
+        );
+
+        Ok(())
+    }
+
+    async fn delete_project(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let mut transaction = pool.begin().await?;
+
+        record_activity(&mut *transaction, id, "deleted").await?;
+
+        // delete the project from the SQL table, the transaction is rolled back on conflict
+        let deleted = sqlx::query!(
+            r#"
+                DELETE FROM projects
+                WHERE id = ? AND version = ?
+            "#,
+            id,
+            version
+        )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        if deleted == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        // adjust the positions of the remaining projects
+        sqlx::query!(
+            r#"
+                UPDATE projects SET position = position - 1
+                WHERE owner_id = ? AND category = ?
+                AND position > ?
+            "#,
+            owner_id,
+            category,
+            position
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - deleted project from {} at position {} with id {}",
+            Local::now(),
+            category,
+            position,
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn move_project_up(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        if position == 1 {
+            println!(
+                "{} - Database - project with id {} already in top position",
+                Local::now(),
+                id
+            );
+            return Ok(());
+        }
+
+        let mut transaction = pool.begin().await?;
+
+        // move the current project up, unless it was changed since the submitted position was read
+        let updated = sqlx::query!(
+            r#"
+                UPDATE projects SET position = position - 1
+                WHERE id = ? AND version = ?
+            "#,
+            id,
+            version
+        )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        // move the above project down
+        sqlx::query!(
+            r#"
+                UPDATE projects SET position = position + 1
+                WHERE owner_id = ? AND category = ? AND position = ? - 1 AND id != ?
+            "#,
+            owner_id,
+            category,
+            position,
+            id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database: - moved project with id {} up to position {}",
+            Local::now(),
+            id,
+            position - 1
+        );
+
+        Ok(())
+    }
+
+    async fn move_project_down(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let highest_position = self
+            .get_highest_position_by_category(owner_id, &category)
+            .await?
+            .map_or(1, |pos| pos);
+
+        if position == highest_position {
+            println!(
+                "{} - Database - project with id {} already in bottom position ({})",
+                Local::now(),
+                id,
+                position
+            );
+            return Ok(());
+        }
+
+        let mut transaction = pool.begin().await?;
+
+        // move the current project down, unless it was changed since the submitted position was read
+        let updated = sqlx::query!(
+            r#"
+                UPDATE projects SET position = position + 1
+                WHERE id = ? AND version = ?
+            "#,
+            id,
+            version
+        )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        // move the below project up
+        sqlx::query!(
+            r#"
+                UPDATE projects SET position = position - 1
+                WHERE owner_id = ? AND category = ? AND position = ? + 1 AND id != ?
+            "#,
+            owner_id,
+            category,
+            position,
+            id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - moved project with id {} down to position {}",
+            Local::now(),
+            id,
+            position + 1
+        );
+
+        Ok(())
+    }
+
+    async fn update_notes(&self, id: u64, notes: String, version: u64) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let updated = sqlx::query!(
+            r#"
+                UPDATE projects SET notes = ?, version = version + 1
+                WHERE id = ? AND version = ?
+            "#,
+            notes,
+            id,
+            version
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        record_activity(pool, id, "notes updated").await?;
+
+        println!(
+            "{} - Database - update notes for project with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn get_webhooks(&self, owner_id: u64) -> anyhow::Result<Vec<models::Webhook>> {
+        let pool = &self.pool;
+        let webhooks = sqlx::query_as!(
+            models::Webhook,
+            r#"
+                SELECT id, url, secret, events, creation_date FROM webhooks
+                WHERE owner_id = ?
+                ORDER BY id
+            "#,
+            owner_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(webhooks)
+    }
+
+    async fn add_webhook(
+        &self,
+        owner_id: u64,
+        url: &str,
+        secret: &str,
+        events: &str,
+    ) -> anyhow::Result<u64> {
+        let pool = &self.pool;
+        let webhook_id = sqlx::query!(
+            r#"
+                INSERT INTO webhooks ( owner_id, url, secret, events )
+                VALUES ( ?, ?, ?, ? )
+            "#,
+            owner_id,
+            url,
+            secret,
+            events
+        )
+        .execute(pool)
+        .await?
+        .last_insert_id();
+
+        println!(
+            "{} - Database - added webhook for {} with id {}",
+            Local::now(),
+            url,
+            webhook_id
+        );
+
+        Ok(webhook_id)
+    }
+
+    async fn delete_webhook(&self, owner_id: u64, id: u64) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        sqlx::query!(
+            r#"
+                DELETE FROM webhooks
+                WHERE id = ? AND owner_id = ?
+            "#,
+            id,
+            owner_id
+        )
+        .execute(pool)
+        .await?;
+
+        println!(
+            "{} - Database - deleted webhook with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn add_webhook_delivery(
+        &self,
+        webhook_id: u64,
+        event: &str,
+        attempt: u64,
+        status_code: Option<u64>,
+        error: &str,
+    ) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let delivered_at = Utc::now().naive_utc();
+        sqlx::query!(
+            r#"
+                INSERT INTO webhook_deliveries ( webhook_id, event, attempt, status_code, error, delivered_at )
+                VALUES ( ?, ?, ?, ?, ?, ? )
+            "#,
+            webhook_id,
+            event,
+            attempt,
+            status_code,
+            error,
+            delivered_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_webhook_deliveries(
+        &self,
+        owner_id: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<models::WebhookDelivery>> {
+        let pool = &self.pool;
+        let deliveries = sqlx::query_as!(
+            models::WebhookDelivery,
+            r#"
+                SELECT * FROM webhook_deliveries
+                WHERE webhook_id IN ( SELECT id FROM webhooks WHERE owner_id = ? )
+                ORDER BY delivered_at DESC, id DESC
+                LIMIT ?
+            "#,
+            owner_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    async fn count_users(&self) -> anyhow::Result<i64> {
+        let pool = &self.pool;
+        let count = sqlx::query!(
+            r#"
+                SELECT COUNT(*) AS total FROM users
+            "#
+        )
+        .fetch_one(pool)
+        .await?
+        .total;
+
+        Ok(count)
+    }
+
+    async fn get_user_by_username(&self, username: &str) -> anyhow::Result<Option<models::User>> {
+        let pool = &self.pool;
+        let user = sqlx::query_as!(
+            models::User,
+            r#"
+                SELECT id, username, password_hash FROM users
+                WHERE username = ?
+            "#,
+            username
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn get_users(&self) -> anyhow::Result<Vec<models::UserName>> {
+        let pool = &self.pool;
+        let users = sqlx::query_as!(
+            models::UserName,
+            r#"
+                SELECT id, username FROM users
+                ORDER BY username
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    async fn add_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+        categories: &[String],
+    ) -> anyhow::Result<u64> {
+        let pool = &self.pool;
+        let mut transaction = pool.begin().await?;
+
+        let user_id = sqlx::query!(
+            r#"
+                INSERT INTO users ( username, password_hash )
+                VALUES ( ?, ? )
+            "#,
+            username,
+            password_hash
+        )
+        .execute(&mut *transaction)
+        .await?
+        .last_insert_id();
+
+        setup_user(&mut transaction, user_id, categories).await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - added user {} with id {}",
+            Local::now(),
+            username,
+            user_id
+        );
+
+        Ok(user_id)
+    }
+
+    async fn add_first_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+        categories: &[String],
+    ) -> anyhow::Result<Option<u64>> {
+        let pool = &self.pool;
+        let mut transaction = pool.begin().await?;
+
+        // the insert locks what it read of the users table, so a concurrent setup waits for this one or fails
+        let result = sqlx::query!(
+            r#"
+                INSERT INTO users ( username, password_hash )
+                SELECT ?, ? FROM DUAL
+                WHERE NOT EXISTS ( SELECT 1 FROM users )
+            "#,
+            username,
+            password_hash
+        )
+        .execute(&mut *transaction)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        let user_id = result.last_insert_id();
+
+        setup_user(&mut transaction, user_id, categories).await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - added first user {} with id {}",
+            Local::now(),
+            username,
+            user_id
+        );
+
+        Ok(Some(user_id))
+    }
+
+    async fn update_password(&self, id: u64, password_hash: &str) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        sqlx::query!(
+            r#"
+                UPDATE users SET password_hash = ?
+                WHERE id = ?
+            "#,
+            password_hash,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        // sign out everywhere after a password change
+        sqlx::query!(
+            r#"
+                DELETE FROM sessions
+                WHERE user_id = ?
+            "#,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        println!(
+            "{} - Database - updated password of user with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn add_session(
+        &self,
+        token_hash: &str,
+        user_id: u64,
+        expires_at: NaiveDateTime,
+    ) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let created_at = Utc::now().naive_utc();
+        sqlx::query!(
+            r#"
+                INSERT INTO sessions ( token_hash, user_id, created_at, expires_at )
+                VALUES ( ?, ?, ?, ? )
+            "#,
+            token_hash,
+            user_id,
+            created_at,
+            expires_at
+        )
+        .execute(pool)
+        .await?;
+
+        // clean up expired sessions while we are at it
+        sqlx::query!(
+            r#"
+                DELETE FROM sessions
+                WHERE expires_at <= ?
+            "#,
+            created_at
+        )
+        .execute(pool)
+        .await?;
+
+        println!(
+            "{} - Database - started session for user with id {}",
+            Local::now(),
+            user_id
+        );
+
+        Ok(())
+    }
+
+    async fn get_session_user(&self, token_hash: &str) -> anyhow::Result<Option<models::User>> {
+        let pool = &self.pool;
+        let now = Utc::now().naive_utc();
+        let user = sqlx::query_as!(
+            models::User,
+            r#"
+                SELECT id, username, password_hash FROM users
+                WHERE id = ( SELECT user_id FROM sessions WHERE token_hash = ? AND expires_at > ? )
+            "#,
+            token_hash,
+            now
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        sqlx::query!(
+            r#"
+                DELETE FROM sessions
+                WHERE token_hash = ?
+            "#,
+            token_hash
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_categories(&self, owner_id: u64) -> anyhow::Result<Vec<models::Category>> {
+        let pool = &self.pool;
+        let categories = sqlx::query_as!(
+            models::Category,
+            r#"
+                SELECT * FROM categories
+                WHERE owner_id = ?
+                ORDER BY id
+            "#,
+            owner_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(categories)
+    }
+
+    async fn get_shared_categories(&self, user_id: u64) -> anyhow::Result<Vec<models::Category>> {
+        let pool = &self.pool;
+        let categories = sqlx::query_as!(
+            models::Category,
+            r#"
+                SELECT * FROM categories
+                WHERE id IN ( SELECT category_id FROM category_shares WHERE user_id = ? )
+                ORDER BY owner_id, id
+            "#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(categories)
+    }
+
+    async fn get_category(&self, id: u64) -> anyhow::Result<Option<models::Category>> {
+        let pool = &self.pool;
+        let category = sqlx::query_as!(
+            models::Category,
+            r#"
+                SELECT * FROM categories
+                WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(category)
+    }
+
+    async fn get_category_by_name(
+        &self,
+        owner_id: u64,
+        name: &str,
+    ) -> anyhow::Result<Option<models::Category>> {
+        let pool = &self.pool;
+        let category = sqlx::query_as!(
+            models::Category,
+            r#"
+                SELECT * FROM categories
+                WHERE owner_id = ? AND name = ?
+            "#,
+            owner_id,
+            name
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(category)
+    }
+
+    async fn add_category(&self, owner_id: u64, name: &str) -> anyhow::Result<()> {
+        insert_category(&self.pool, owner_id, name).await
+    }
+
+    async fn delete_category(&self, owner_id: u64, id: u64) -> anyhow::Result<bool> {
+        let pool = &self.pool;
+        let deleted = sqlx::query!(
+            r#"
+                DELETE FROM categories
+                WHERE id = ? AND owner_id = ?
+                AND NOT EXISTS (
+                    SELECT id FROM projects
+                    WHERE projects.owner_id = categories.owner_id AND projects.category = categories.name
+                )
+            "#,
+            id,
+            owner_id
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+        println!(
+            "{} - Database - deleted category with id {}: {}",
+            Local::now(),
+            id,
+            deleted > 0
+        );
+
+        Ok(deleted > 0)
+    }
+
+    async fn get_category_shares(
+        &self,
+        owner_id: u64,
+    ) -> anyhow::Result<Vec<models::CategoryShare>> {
+        let pool = &self.pool;
+        let shares = sqlx::query_as!(
+            models::CategoryShare,
+            r#"
+                SELECT * FROM category_shares
+                WHERE category_id IN ( SELECT id FROM categories WHERE owner_id = ? )
+                ORDER BY category_id, id
+            "#,
+            owner_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(shares)
+    }
+
+    async fn share_category(
+        &self,
+        category_id: u64,
+        user_id: u64,
+        access: models::Access,
+    ) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let access = access.as_str();
+        sqlx::query!(
+            r#"
+                INSERT INTO category_shares ( category_id, user_id, access )
+                VALUES ( ?, ?, ? )
+                ON DUPLICATE KEY UPDATE access = ?
+            "#,
+            category_id,
+            user_id,
+            access,
+            access
+        )
+        .execute(pool)
+        .await?;
+
+        println!(
+            "{} - Database - shared category with id {} with user with id {} ({})",
+            Local::now(),
+            category_id,
+            user_id,
+            access
+        );
+
+        Ok(())
+    }
+
+    async fn delete_share(&self, owner_id: u64, id: u64) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        sqlx::query!(
+            r#"
+                DELETE FROM category_shares
+                WHERE id = ?
+                AND category_id IN ( SELECT id FROM categories WHERE owner_id = ? )
+            "#,
+            id,
+            owner_id
+        )
+        .execute(pool)
+        .await?;
+
+        println!(
+            "{} - Database - deleted category share with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn get_access(
+        &self,
+        user_id: u64,
+        owner_id: u64,
+        category: &str,
+    ) -> anyhow::Result<Option<models::Access>> {
+        let pool = &self.pool;
+        if user_id == owner_id {
+            return Ok(Some(models::Access::Edit));
+        }
+
+        let share = sqlx::query!(
+            r#"
+                SELECT access FROM category_shares
+                WHERE user_id = ?
+                AND category_id = ( SELECT id FROM categories WHERE owner_id = ? AND name = ? )
+            "#,
+            user_id,
+            owner_id,
+            category
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(share.and_then(|share| models::Access::parse(&share.access)))
+    }
+
+    async fn restore_projects(
+        &self,
+        owner_id: u64,
+        projects: Vec<models::Project>,
+    ) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let mut transaction = pool.begin().await?;
+
+        // delete all projects of the user from the SQL table
+        sqlx::query!(
+            r#"
+                DELETE FROM projects
+                WHERE owner_id = ?
+            "#,
+            owner_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        // insert all projects from the backup
+        for project in &projects {
+            sqlx::query!(
+                r#"
+                    INSERT INTO projects ( owner_id, name, category, position, status, creation_date, start_date, completion_date, notes )
+                    VALUES ( ?, ?, ?, ?, ?, ?, ?, ?, ? )
+                "#,
+                owner_id,
+                project.name,
+                project.category,
+                project.position,
+                project.status,
+                project.creation_date,
+                project.start_date,
+                project.completion_date,
+                project.notes
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+        println!(
+            "{} - Database - restored {} projects from backup",
+            Local::now(),
+            projects.len()
+        );
+
+        Ok(())
+    }
+
+    async fn get_user(&self, id: u64) -> anyhow::Result<Option<models::User>> {
+        let pool = &self.pool;
+        let user = sqlx::query_as!(
+            models::User,
+            r#"
+                SELECT id, username, password_hash FROM users
+                WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn get_calendar_token_hash(&self, user_id: u64) -> anyhow::Result<Option<String>> {
+        let pool = &self.pool;
+        let token_hash = sqlx::query_scalar!(
+            r#"
+                SELECT calendar_token_hash FROM users
+                WHERE id = ?
+            "#,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+
+        Ok(token_hash)
+    }
+
+    async fn set_calendar_token_hash(&self, user_id: u64, token_hash: &str) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        sqlx::query!(
+            r#"
+                UPDATE users
+                SET calendar_token_hash = ?
+                WHERE id = ?
+            "#,
+            token_hash,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+        println!(
+            "{} - Database - replaced calendar token of user with id {}",
+            Local::now(),
+            user_id
+        );
+
+        Ok(())
+    }
+
+    async fn get_api_tokens(&self, user_id: u64) -> anyhow::Result<Vec<models::ApiToken>> {
+        let pool = &self.pool;
+        let tokens = sqlx::query_as!(
+            models::ApiToken,
+            r#"
+                SELECT id, user_id, name, scopes, created_at, last_used FROM api_tokens
+                WHERE user_id = ?
+                ORDER BY id
+            "#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    async fn add_api_token(
+        &self,
+        user_id: u64,
+        name: &str,
+        token_hash: &str,
+        scopes: &str,
+    ) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        let created_at = Utc::now().naive_utc();
+        sqlx::query!(
+            r#"
+                INSERT INTO api_tokens ( user_id, name, token_hash, scopes, created_at )
+                VALUES ( ?, ?, ?, ?, ? )
+            "#,
+            user_id,
+            name,
+            token_hash,
+            scopes,
+            created_at
+        )
+        .execute(pool)
+        .await?;
+
+        println!(
+            "{} - Database - added api token {} for user with id {} ({})",
+            Local::now(),
+            name,
+            user_id,
+            scopes
+        );
+
+        Ok(())
+    }
+
+    async fn delete_api_token(&self, user_id: u64, id: u64) -> anyhow::Result<()> {
+        let pool = &self.pool;
+        sqlx::query!(
+            r#"
+                DELETE FROM api_tokens
+                WHERE id = ? AND user_id = ?
+            "#,
+            id,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        println!(
+            "{} - Database - revoked api token with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn use_api_token(&self, token_hash: &str) -> anyhow::Result<Option<models::ApiToken>> {
+        let pool = &self.pool;
+        let token = sqlx::query_as!(
+            models::ApiToken,
+            r#"
+                SELECT id, user_id, name, scopes, created_at, last_used FROM api_tokens
+                WHERE token_hash = ?
+            "#,
+            token_hash
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(token) = &token {
+            let now = Utc::now().naive_utc();
+            sqlx::query!(
+                r#"
+                    UPDATE api_tokens
+                    SET last_used = ?
+                    WHERE id = ?
+                "#,
+                now,
+                token.id
+            )
+            .execute(pool)
+            .await?;
+        }
+
+        Ok(token)
+    }
+}
@@ -0,0 +1,1604 @@
+use async_trait::async_trait;
+use chrono::{Local, NaiveDate, NaiveDateTime, Utc};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqlitePool};
+use sqlx::QueryBuilder;
+use std::str::FromStr;
+
+use super::{Repository, VersionConflict};
+use crate::models;
+
+static MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite");
+
+// local database file, no database server needed
+// queries are checked at runtime, the offline query cache only describes the MySQL queries
+// sqlite has no unsigned integers, ids and positions are bound as i64
+pub struct SqliteRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteRepository {
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await?;
+        Ok(SqliteRepository { pool })
+    }
+}
+
+// record activity event for a project, must run before the project is deleted
+async fn record_activity<'e, E>(executor: E, id: u64, event: &str) -> anyhow::Result<()>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let created_at = Utc::now().naive_utc();
+    sqlx::query(
+        r#"
+            INSERT INTO activity ( project_id, owner_id, project_name, category, event, created_at )
+            SELECT id, owner_id, name, category, ?, ?
+            FROM projects
+            WHERE id = ?
+        "#,
+    )
+    .bind(event)
+    .bind(created_at)
+    .bind(id as i64)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+// position after the last project of a category, new and imported projects are added at the end
+async fn next_position<'e, E>(executor: E, owner_id: u64, category: &str) -> anyhow::Result<u64>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let highest_position = sqlx::query_scalar::<_, Option<i64>>(
+        r#"
+            SELECT MAX(position) AS max_position
+            FROM projects
+            WHERE owner_id = ? AND category = ?
+        "#,
+    )
+    .bind(owner_id as i64)
+    .bind(category)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(highest_position.map_or(1, |position| position as u64 + 1))
+}
+
+// add category, existing categories are left alone
+async fn insert_category<'e, E>(executor: E, owner_id: u64, name: &str) -> anyhow::Result<()>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    sqlx::query(
+        r#"
+            INSERT OR IGNORE INTO categories ( owner_id, name )
+            VALUES ( ?, ? )
+        "#,
+    )
+    .bind(owner_id as i64)
+    .bind(name)
+    .execute(executor)
+    .await?;
+
+    println!(
+        "{} - Database - added category {} for user with id {}",
+        Local::now(),
+        name,
+        owner_id
+    );
+
+    Ok(())
+}
+
+// first categories of a new user, data from before accounts existed (owner 0) is handed to it
+async fn setup_user(
+    transaction: &mut sqlx::Transaction<'_, Sqlite>,
+    user_id: u64,
+    categories: &[String],
+) -> anyhow::Result<()> {
+    for category in categories {
+        insert_category(&mut **transaction, user_id, category).await?;
+    }
+
+    for table in ["projects", "activity", "webhooks"] {
+        sqlx::query(&format!(
+            "UPDATE {} SET owner_id = ? WHERE owner_id = 0",
+            table
+        ))
+        .bind(user_id as i64)
+        .execute(&mut **transaction)
+        .await?;
+    }
+    sqlx::query(
+        r#"
+            INSERT OR IGNORE INTO categories ( owner_id, name )
+            SELECT DISTINCT owner_id, category FROM projects
+            WHERE owner_id = ?
+        "#,
+    )
+    .bind(user_id as i64)
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl Repository for SqliteRepository {
+    async fn migrate(&self) -> anyhow::Result<()> {
+        super::migrate(&MIGRATOR, &self.pool).await
+    }
+
+    async fn get_projects(&self, owner_id: u64) -> anyhow::Result<Vec<models::Project>> {
+        let projects = sqlx::query_as::<_, models::Project>(
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ?
+                ORDER BY position
+            "#,
+        )
+        .bind(owner_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} projects",
+            Local::now(),
+            projects.len()
+        );
+        Ok(projects)
+    }
+
+    async fn get_category_projects(
+        &self,
+        owner_id: u64,
+        category: &str,
+    ) -> anyhow::Result<Vec<models::Project>> {
+        let projects = sqlx::query_as::<_, models::Project>(
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ? AND category = ?
+                ORDER BY position
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(category)
+        .fetch_all(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} projects in {}",
+            Local::now(),
+            projects.len(),
+            category
+        );
+        Ok(projects)
+    }
+
+    async fn get_project(&self, id: u64) -> anyhow::Result<Option<models::Project>> {
+        let project = sqlx::query_as::<_, models::Project>(
+            r#"
+                SELECT * FROM projects
+                WHERE id = ?
+            "#,
+        )
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched project with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(project)
+    }
+
+    async fn get_recent_activity(
+        &self,
+        owner_id: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<models::Activity>> {
+        let activity = sqlx::query_as::<_, models::Activity>(
+            r#"
+                SELECT id, project_id, project_name, category, event, created_at FROM activity
+                WHERE owner_id = ?
+                ORDER BY created_at DESC, id DESC
+                LIMIT ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} activity events",
+            Local::now(),
+            activity.len()
+        );
+        Ok(activity)
+    }
+
+    async fn start_project(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        // dates are stored as text, bind the date only so it compares with the other dates
+        let current_date = Local::now().date_naive();
+        let updated = sqlx::query(
+            r#"
+                UPDATE projects
+                SET status = 1, position = 1, start_date = ?, version = version + 1
+                WHERE id = ? AND version = ?
+            "#,
+        )
+        .bind(current_date)
+        .bind(id as i64)
+        .bind(version as i64)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        sqlx::query(
+            r#"
+                UPDATE projects SET position = position + 1
+                WHERE owner_id = ? AND category = ?
+                AND position <= ?
+                AND id != ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(&category)
+        .bind(position as i64)
+        .bind(id as i64)
+        .execute(&mut *transaction)
+        .await?;
+
+        record_activity(&mut *transaction, id, "started").await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - started project with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn complete_project(&self, id: u64, version: u64) -> anyhow::Result<()> {
+        let current_date = Local::now().date_naive();
+        let updated = sqlx::query(
+            r#"
+                UPDATE projects SET status = 2, completion_date = ?, version = version + 1
+                WHERE id = ? AND version = ?
+            "#,
+        )
+        .bind(current_date)
+        .bind(id as i64)
+        .bind(version as i64)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        record_activity(&self.pool, id, "completed").await?;
+
+        println!(
+            "{} - Database - completed project with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn get_timeline_projects(
+        &self,
+        owner_id: u64,
+        start: NaiveDate,
+        end: NaiveDate,
+        categories: &[String],
+        in_progress: bool,
+    ) -> anyhow::Result<Vec<models::Project>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = "#,
+        );
+        query
+            .push_bind(owner_id as i64)
+            .push(" AND ( status = 2 OR ( ")
+            .push_bind(in_progress)
+            .push(" AND status = 1 ) ) AND start_date <= ")
+            .push_bind(end)
+            .push(" AND ( status = 1 OR completion_date >= ")
+            .push_bind(start)
+            .push(" )");
+        // one placeholder per category
+        if !categories.is_empty() {
+            query.push(" AND category IN ( ");
+            let mut list = query.separated(", ");
+            for category in categories {
+                list.push_bind(category);
+            }
+            query.push(" )");
+        }
+        query.push(" ORDER BY start_date, id");
+        let projects = query
+            .build_query_as::<models::Project>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        println!(
+            "{} - Database - fetched {} timeline projects between {} and {}",
+            Local::now(),
+            projects.len(),
+            start,
+            end
+        );
+        Ok(projects)
+    }
+
+    async fn get_completed_projects(
+        &self,
+        owner_id: u64,
+        block: u64,
+        page_size: u64,
+    ) -> anyhow::Result<models::CompletedPage> {
+        let offset = block.saturating_sub(1) * page_size;
+
+        let projects = sqlx::query_as::<_, models::Project>(
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ? AND status = 2
+                ORDER BY completion_date DESC, id DESC
+                LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(page_size as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = sqlx::query_scalar::<_, i64>(
+            r#"
+                SELECT COUNT(*) AS total
+                FROM projects
+                WHERE owner_id = ? AND status = 2
+            "#,
+        )
+        .bind(owner_id as i64)
+        .fetch_one(&self.pool)
+        .await? as u64;
+
+        println!(
+            "{} - Database - fetched block {} of completed projects",
+            Local::now(),
+            block
+        );
+
+        Ok(models::CompletedPage {
+            has_more: offset + (projects.len() as u64) < total,
+            projects,
+            total,
+        })
+    }
+
+    async fn get_completed_projects_by_year(
+        &self,
+        owner_id: u64,
+        year: i32,
+    ) -> anyhow::Result<Vec<models::Project>> {
+        let projects = sqlx::query_as::<_, models::Project>(
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ? AND status = 2
+                AND CAST(strftime('%Y', completion_date) AS INTEGER) = ?
+                ORDER BY completion_date, id
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(year)
+        .fetch_all(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} projects completed in {}",
+            Local::now(),
+            projects.len(),
+            year
+        );
+        Ok(projects)
+    }
+
+    async fn get_completed_per_month(
+        &self,
+        owner_id: u64,
+        since: NaiveDate,
+    ) -> anyhow::Result<Vec<models::CompletedPerMonth>> {
+        let completed = sqlx::query_as::<_, models::CompletedPerMonth>(
+            r#"
+                SELECT strftime('%Y-%m', completion_date) AS month, category, COUNT(*) AS completed
+                FROM projects
+                WHERE owner_id = ? AND status = 2
+                AND completion_date >= ?
+                GROUP BY month, category
+                ORDER BY month DESC, category
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched completed projects per month since {}",
+            Local::now(),
+            since
+        );
+        Ok(completed)
+    }
+
+    async fn get_duration_stats(&self, owner_id: u64) -> anyhow::Result<models::DurationStats> {
+        // nearest rank percentiles like the MySQL query, julianday differences replace DATEDIFF
+        let stats = sqlx::query_as::<_, models::DurationStats>(
+            r#"
+                SELECT
+                    MIN(CASE WHEN cycle_rank >= 0.5 THEN cycle_days END) AS cycle_median,
+                    MIN(CASE WHEN cycle_rank >= 0.9 THEN cycle_days END) AS cycle_p90,
+                    MIN(CASE WHEN lead_rank >= 0.5 THEN lead_days END) AS lead_median,
+                    MIN(CASE WHEN lead_rank >= 0.9 THEN lead_days END) AS lead_p90
+                FROM (
+                    SELECT
+                        cycle_days,
+                        lead_days,
+                        CUME_DIST() OVER (ORDER BY cycle_days) AS cycle_rank,
+                        CUME_DIST() OVER (ORDER BY lead_days) AS lead_rank
+                    FROM (
+                        SELECT
+                            CAST(julianday(completion_date) - julianday(start_date) AS INTEGER) AS cycle_days,
+                            CAST(julianday(completion_date) - julianday(creation_date) AS INTEGER) AS lead_days
+                        FROM projects
+                        WHERE owner_id = ? AND status = 2
+                    )
+                ) AS durations
+            "#,
+        )
+        .bind(owner_id as i64)
+        .fetch_one(&self.pool)
+        .await?;
+
+        println!("{} - Database - fetched duration statistics", Local::now());
+        Ok(stats)
+    }
+
+    async fn get_work_in_progress(
+        &self,
+        owner_id: u64,
+    ) -> anyhow::Result<Vec<models::CategoryCount>> {
+        let wip = sqlx::query_as::<_, models::CategoryCount>(
+            r#"
+                SELECT category, COUNT(*) AS total
+                FROM projects
+                WHERE owner_id = ? AND status = 1
+                GROUP BY category
+                ORDER BY category
+            "#,
+        )
+        .bind(owner_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        println!("{} - Database - fetched work in progress", Local::now());
+        Ok(wip)
+    }
+
+    async fn get_backlog_age(
+        &self,
+        owner_id: u64,
+        cutoffs: [NaiveDate; 4],
+    ) -> anyhow::Result<Vec<models::AgeBucket>> {
+        let buckets = sqlx::query_as::<_, models::AgeBucket>(
+            r#"
+                SELECT
+                    CASE
+                        WHEN creation_date > ? THEN 0
+                        WHEN creation_date > ? THEN 1
+                        WHEN creation_date > ? THEN 2
+                        WHEN creation_date > ? THEN 3
+                        ELSE 4
+                    END AS bucket,
+                    COUNT(*) AS total
+                FROM projects
+                WHERE owner_id = ? AND status = 0
+                GROUP BY bucket
+                ORDER BY bucket
+            "#,
+        )
+        .bind(cutoffs[0])
+        .bind(cutoffs[1])
+        .bind(cutoffs[2])
+        .bind(cutoffs[3])
+        .bind(owner_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched backlog age distribution",
+            Local::now()
+        );
+        Ok(buckets)
+    }
+
+    async fn get_oldest_backlog(
+        &self,
+        owner_id: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<models::Project>> {
+        let projects = sqlx::query_as::<_, models::Project>(
+            r#"
+                SELECT * FROM projects
+                WHERE owner_id = ? AND status = 0
+                ORDER BY creation_date, id
+                LIMIT ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - fetched {} oldest backlog projects",
+            Local::now(),
+            projects.len()
+        );
+        Ok(projects)
+    }
+
+    async fn get_highest_position_by_category(
+        &self,
+        owner_id: u64,
+        category: &str,
+    ) -> anyhow::Result<Option<u64>> {
+        let max_position = sqlx::query_scalar::<_, Option<i64>>(
+            r#"
+                SELECT MAX(position) AS max_position
+                FROM projects
+                WHERE owner_id = ? AND category = ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(category)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(max_position.map(|position| position.unwrap_or(0) as u64))
+    }
+
+    async fn add_project(
+        &self,
+        owner_id: u64,
+        name: String,
+        category: String,
+    ) -> anyhow::Result<u64> {
+        let position = next_position(&self.pool, owner_id, &category).await?;
+
+        let project_id = sqlx::query(
+            r#"
+                INSERT INTO projects ( owner_id, name, category, position )
+                VALUES ( ?, ?, ?, ? )
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(&name)
+        .bind(&category)
+        .bind(position as i64)
+        .execute(&self.pool)
+        .await?
+        .last_insert_rowid() as u64;
+
+        record_activity(&self.pool, project_id, "added").await?;
+
+        println!(
+            "{} - Database - added {} to {} at position {} with id {}",
+            Local::now(),
+            name,
+            category,
+            position,
+            project_id
+        );
+
+        Ok(project_id)
+    }
+
+    async fn import_projects(
+        &self,
+        owner_id: u64,
+        projects: &[models::NewProject],
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        for project in projects {
+            let position = next_position(&mut *transaction, owner_id, &project.category).await?;
+
+            let project_id = sqlx::query(
+                r#"
+                    INSERT INTO projects ( owner_id, name, category, position, status, notes, creation_date, start_date, completion_date )
+                    VALUES ( ?, ?, ?, ?, ?, ?, ?, ?, ? )
+                "#,
+            )
+            .bind(owner_id as i64)
+            .bind(&project.name)
+            .bind(&project.category)
+            .bind(position as i64)
+            .bind(project.status as i64)
+            .bind(&project.notes)
+            .bind(project.creation_date)
+            .bind(project.start_date)
+            .bind(project.completion_date)
+            .execute(&mut *transaction)
+            .await?
+            .last_insert_rowid() as u64;
+
+            record_activity(&mut *transaction, project_id, "imported").await?;
+
+            println!(
+                "{} - Database - imported {} to {} at position {} with id {}",
+                Local::now(),
+                project.name,
+                project.category,
+                position,
+                project_id
+            );
+        }
+
+        transaction.commit().await?;
+        println!(
+            "{} - Database - imported {} projects",
+            Local::now(),
+            projects.len()
+        );
+
+        Ok(())
+    }
+
+    async fn delete_project(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        record_activity(&mut *transaction, id, "deleted").await?;
+
+        let deleted = sqlx::query(
+            r#"
+                DELETE FROM projects
+                WHERE id = ? AND version = ?
+            "#,
+        )
+        .bind(id as i64)
+        .bind(version as i64)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        if deleted == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        sqlx::query(
+            r#"
+                UPDATE projects SET position = position - 1
+                WHERE owner_id = ? AND category = ?
+                AND position > ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(&category)
+        .bind(position as i64)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - deleted project from {} at position {} with id {}",
+            Local::now(),
+            category,
+            position,
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn move_project_up(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        if position == 1 {
+            println!(
+                "{} - Database - project with id {} already in top position",
+                Local::now(),
+                id
+            );
+            return Ok(());
+        }
+
+        let mut transaction = self.pool.begin().await?;
+
+        let updated = sqlx::query(
+            r#"
+                UPDATE projects SET position = position - 1
+                WHERE id = ? AND version = ?
+            "#,
+        )
+        .bind(id as i64)
+        .bind(version as i64)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        sqlx::query(
+            r#"
+                UPDATE projects SET position = position + 1
+                WHERE owner_id = ? AND category = ? AND position = ? - 1 AND id != ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(&category)
+        .bind(position as i64)
+        .bind(id as i64)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - moved project with id {} up to position {}",
+            Local::now(),
+            id,
+            position - 1
+        );
+
+        Ok(())
+    }
+
+    async fn move_project_down(
+        &self,
+        id: u64,
+        owner_id: u64,
+        category: String,
+        position: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let highest_position = self
+            .get_highest_position_by_category(owner_id, &category)
+            .await?
+            .map_or(1, |pos| pos);
+
+        if position == highest_position {
+            println!(
+                "{} - Database - project with id {} already in bottom position ({})",
+                Local::now(),
+                id,
+                position
+            );
+            return Ok(());
+        }
+
+        let mut transaction = self.pool.begin().await?;
+
+        let updated = sqlx::query(
+            r#"
+                UPDATE projects SET position = position + 1
+                WHERE id = ? AND version = ?
+            "#,
+        )
+        .bind(id as i64)
+        .bind(version as i64)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        sqlx::query(
+            r#"
+                UPDATE projects SET position = position - 1
+                WHERE owner_id = ? AND category = ? AND position = ? + 1 AND id != ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(&category)
+        .bind(position as i64)
+        .bind(id as i64)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - moved project with id {} down to position {}",
+            Local::now(),
+            id,
+            position + 1
+        );
+
+        Ok(())
+    }
+
+    async fn update_notes(&self, id: u64, notes: String, version: u64) -> anyhow::Result<()> {
+        let updated = sqlx::query(
+            r#"
+                UPDATE projects SET notes = ?, version = version + 1
+                WHERE id = ? AND version = ?
+            "#,
+        )
+        .bind(&notes)
+        .bind(id as i64)
+        .bind(version as i64)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VersionConflict { id }.into());
+        }
+
+        record_activity(&self.pool, id, "notes updated").await?;
+
+        println!(
+            "{} - Database - update notes for project with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn get_webhooks(&self, owner_id: u64) -> anyhow::Result<Vec<models::Webhook>> {
+        let webhooks = sqlx::query_as::<_, models::Webhook>(
+            r#"
+                SELECT id, url, secret, events, creation_date FROM webhooks
+                WHERE owner_id = ?
+                ORDER BY id
+            "#,
+        )
+        .bind(owner_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(webhooks)
+    }
+
+    async fn add_webhook(
+        &self,
+        owner_id: u64,
+        url: &str,
+        secret: &str,
+        events: &str,
+    ) -> anyhow::Result<u64> {
+        let webhook_id = sqlx::query(
+            r#"
+                INSERT INTO webhooks ( owner_id, url, secret, events )
+                VALUES ( ?, ?, ?, ? )
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(url)
+        .bind(secret)
+        .bind(events)
+        .execute(&self.pool)
+        .await?
+        .last_insert_rowid() as u64;
+
+        println!(
+            "{} - Database - added webhook for {} with id {}",
+            Local::now(),
+            url,
+            webhook_id
+        );
+
+        Ok(webhook_id)
+    }
+
+    async fn delete_webhook(&self, owner_id: u64, id: u64) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+                DELETE FROM webhooks
+                WHERE id = ? AND owner_id = ?
+            "#,
+        )
+        .bind(id as i64)
+        .bind(owner_id as i64)
+        .execute(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - deleted webhook with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn add_webhook_delivery(
+        &self,
+        webhook_id: u64,
+        event: &str,
+        attempt: u64,
+        status_code: Option<u64>,
+        error: &str,
+    ) -> anyhow::Result<()> {
+        let delivered_at = Utc::now().naive_utc();
+        sqlx::query(
+            r#"
+                INSERT INTO webhook_deliveries ( webhook_id, event, attempt, status_code, error, delivered_at )
+                VALUES ( ?, ?, ?, ?, ?, ? )
+            "#,
+        )
+        .bind(webhook_id as i64)
+        .bind(event)
+        .bind(attempt as i64)
+        .bind(status_code.map(|status_code| status_code as i64))
+        .bind(error)
+        .bind(delivered_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_webhook_deliveries(
+        &self,
+        owner_id: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<models::WebhookDelivery>> {
+        let deliveries = sqlx::query_as::<_, models::WebhookDelivery>(
+            r#"
+                SELECT * FROM webhook_deliveries
+                WHERE webhook_id IN ( SELECT id FROM webhooks WHERE owner_id = ? )
+                ORDER BY delivered_at DESC, id DESC
+                LIMIT ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    async fn count_users(&self) -> anyhow::Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+                SELECT COUNT(*) AS total FROM users
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn get_user_by_username(&self, username: &str) -> anyhow::Result<Option<models::User>> {
+        let user = sqlx::query_as::<_, models::User>(
+            r#"
+                SELECT id, username, password_hash FROM users
+                WHERE username = ?
+            "#,
+        )
+        .bind(username)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn get_users(&self) -> anyhow::Result<Vec<models::UserName>> {
+        let users = sqlx::query_as::<_, models::UserName>(
+            r#"
+                SELECT id, username FROM users
+                ORDER BY username
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    async fn add_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+        categories: &[String],
+    ) -> anyhow::Result<u64> {
+        let mut transaction = self.pool.begin().await?;
+
+        let user_id = sqlx::query(
+            r#"
+                INSERT INTO users ( username, password_hash )
+                VALUES ( ?, ? )
+            "#,
+        )
+        .bind(username)
+        .bind(password_hash)
+        .execute(&mut *transaction)
+        .await?
+        .last_insert_rowid() as u64;
+
+        setup_user(&mut transaction, user_id, categories).await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - added user {} with id {}",
+            Local::now(),
+            username,
+            user_id
+        );
+
+        Ok(user_id)
+    }
+
+    async fn add_first_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+        categories: &[String],
+    ) -> anyhow::Result<Option<u64>> {
+        let mut transaction = self.pool.begin().await?;
+
+        // a single statement, sqlite runs one writer at a time
+        let result = sqlx::query(
+            r#"
+                INSERT INTO users ( username, password_hash )
+                SELECT ?, ?
+                WHERE NOT EXISTS ( SELECT 1 FROM users )
+            "#,
+        )
+        .bind(username)
+        .bind(password_hash)
+        .execute(&mut *transaction)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        let user_id = result.last_insert_rowid() as u64;
+
+        setup_user(&mut transaction, user_id, categories).await?;
+
+        transaction.commit().await?;
+
+        println!(
+            "{} - Database - added first user {} with id {}",
+            Local::now(),
+            username,
+            user_id
+        );
+
+        Ok(Some(user_id))
+    }
+
+    async fn update_password(&self, id: u64, password_hash: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE users SET password_hash = ?
+                WHERE id = ?
+            "#,
+        )
+        .bind(password_hash)
+        .bind(id as i64)
+        .execute(&self.pool)
+        .await?;
+
+        // sign out everywhere after a password change
+        sqlx::query(
+            r#"
+                DELETE FROM sessions
+                WHERE user_id = ?
+            "#,
+        )
+        .bind(id as i64)
+        .execute(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - updated password of user with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn add_session(
+        &self,
+        token_hash: &str,
+        user_id: u64,
+        expires_at: NaiveDateTime,
+    ) -> anyhow::Result<()> {
+        let created_at = Utc::now().naive_utc();
+        sqlx::query(
+            r#"
+                INSERT INTO sessions ( token_hash, user_id, created_at, expires_at )
+                VALUES ( ?, ?, ?, ? )
+            "#,
+        )
+        .bind(token_hash)
+        .bind(user_id as i64)
+        .bind(created_at)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                DELETE FROM sessions
+                WHERE expires_at <= ?
+            "#,
+        )
+        .bind(created_at)
+        .execute(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - started session for user with id {}",
+            Local::now(),
+            user_id
+        );
+
+        Ok(())
+    }
+
+    async fn get_session_user(&self, token_hash: &str) -> anyhow::Result<Option<models::User>> {
+        let now = Utc::now().naive_utc();
+        let user = sqlx::query_as::<_, models::User>(
+            r#"
+                SELECT id, username, password_hash FROM users
+                WHERE id = ( SELECT user_id FROM sessions WHERE token_hash = ? AND expires_at > ? )
+            "#,
+        )
+        .bind(token_hash)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+                DELETE FROM sessions
+                WHERE token_hash = ?
+            "#,
+        )
+        .bind(token_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_categories(&self, owner_id: u64) -> anyhow::Result<Vec<models::Category>> {
+        let categories = sqlx::query_as::<_, models::Category>(
+            r#"
+                SELECT * FROM categories
+                WHERE owner_id = ?
+                ORDER BY id
+            "#,
+        )
+        .bind(owner_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(categories)
+    }
+
+    async fn get_shared_categories(&self, user_id: u64) -> anyhow::Result<Vec<models::Category>> {
+        let categories = sqlx::query_as::<_, models::Category>(
+            r#"
+                SELECT * FROM categories
+                WHERE id IN ( SELECT category_id FROM category_shares WHERE user_id = ? )
+                ORDER BY owner_id, id
+            "#,
+        )
+        .bind(user_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(categories)
+    }
+
+    async fn get_category(&self, id: u64) -> anyhow::Result<Option<models::Category>> {
+        let category = sqlx::query_as::<_, models::Category>(
+            r#"
+                SELECT * FROM categories
+                WHERE id = ?
+            "#,
+        )
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(category)
+    }
+
+    async fn get_category_by_name(
+        &self,
+        owner_id: u64,
+        name: &str,
+    ) -> anyhow::Result<Option<models::Category>> {
+        let category = sqlx::query_as::<_, models::Category>(
+            r#"
+                SELECT * FROM categories
+                WHERE owner_id = ? AND name = ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(category)
+    }
+
+    async fn add_category(&self, owner_id: u64, name: &str) -> anyhow::Result<()> {
+        insert_category(&self.pool, owner_id, name).await
+    }
+
+    async fn delete_category(&self, owner_id: u64, id: u64) -> anyhow::Result<bool> {
+        let deleted = sqlx::query(
+            r#"
+                DELETE FROM categories
+                WHERE id = ? AND owner_id = ?
+                AND NOT EXISTS (
+                    SELECT id FROM projects
+                    WHERE projects.owner_id = categories.owner_id AND projects.category = categories.name
+                )
+            "#,
+        )
+        .bind(id as i64)
+        .bind(owner_id as i64)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        println!(
+            "{} - Database - deleted category with id {}: {}",
+            Local::now(),
+            id,
+            deleted > 0
+        );
+
+        Ok(deleted > 0)
+    }
+
+    async fn get_category_shares(
+        &self,
+        owner_id: u64,
+    ) -> anyhow::Result<Vec<models::CategoryShare>> {
+        let shares = sqlx::query_as::<_, models::CategoryShare>(
+            r#"
+                SELECT * FROM category_shares
+                WHERE category_id IN ( SELECT id FROM categories WHERE owner_id = ? )
+                ORDER BY category_id, id
+            "#,
+        )
+        .bind(owner_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(shares)
+    }
+
+    async fn share_category(
+        &self,
+        category_id: u64,
+        user_id: u64,
+        access: models::Access,
+    ) -> anyhow::Result<()> {
+        let access = access.as_str();
+        sqlx::query(
+            r#"
+                INSERT INTO category_shares ( category_id, user_id, access )
+                VALUES ( ?, ?, ? )
+                ON CONFLICT ( category_id, user_id ) DO UPDATE SET access = excluded.access
+            "#,
+        )
+        .bind(category_id as i64)
+        .bind(user_id as i64)
+        .bind(access)
+        .execute(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - shared category with id {} with user with id {} ({})",
+            Local::now(),
+            category_id,
+            user_id,
+            access
+        );
+
+        Ok(())
+    }
+
+    async fn delete_share(&self, owner_id: u64, id: u64) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+                DELETE FROM category_shares
+                WHERE id = ?
+                AND category_id IN ( SELECT id FROM categories WHERE owner_id = ? )
+            "#,
+        )
+        .bind(id as i64)
+        .bind(owner_id as i64)
+        .execute(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - deleted category share with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn get_access(
+        &self,
+        user_id: u64,
+        owner_id: u64,
+        category: &str,
+    ) -> anyhow::Result<Option<models::Access>> {
+        if user_id == owner_id {
+            return Ok(Some(models::Access::Edit));
+        }
+
+        let access = sqlx::query_scalar::<_, String>(
+            r#"
+                SELECT access FROM category_shares
+                WHERE user_id = ?
+                AND category_id = ( SELECT id FROM categories WHERE owner_id = ? AND name = ? )
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(owner_id as i64)
+        .bind(category)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(access.and_then(|access| models::Access::parse(&access)))
+    }
+
+    async fn restore_projects(
+        &self,
+        owner_id: u64,
+        projects: Vec<models::Project>,
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+                DELETE FROM projects
+                WHERE owner_id = ?
+            "#,
+        )
+        .bind(owner_id as i64)
+        .execute(&mut *transaction)
+        .await?;
+
+        for project in &projects {
+            sqlx::query(
+                r#"
+                    INSERT INTO projects ( owner_id, name, category, position, status, creation_date, start_date, completion_date, notes )
+                    VALUES ( ?, ?, ?, ?, ?, ?, ?, ?, ? )
+                "#,
+            )
+            .bind(owner_id as i64)
+            .bind(&project.name)
+            .bind(&project.category)
+            .bind(project.position as i64)
+            .bind(project.status as i64)
+            .bind(project.creation_date)
+            .bind(project.start_date)
+            .bind(project.completion_date)
+            .bind(&project.notes)
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+        println!(
+            "{} - Database - restored {} projects from backup",
+            Local::now(),
+            projects.len()
+        );
+
+        Ok(())
+    }
+
+    async fn get_user(&self, id: u64) -> anyhow::Result<Option<models::User>> {
+        let user = sqlx::query_as::<_, models::User>(
+            r#"
+                SELECT id, username, password_hash FROM users
+                WHERE id = ?
+            "#,
+        )
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn get_calendar_token_hash(&self, user_id: u64) -> anyhow::Result<Option<String>> {
+        let token_hash = sqlx::query_scalar::<_, Option<String>>(
+            r#"
+                SELECT calendar_token_hash FROM users
+                WHERE id = ?
+            "#,
+        )
+        .bind(user_id as i64)
+        .fetch_optional(&self.pool)
+        .await?
+        .flatten();
+
+        Ok(token_hash)
+    }
+
+    async fn set_calendar_token_hash(&self, user_id: u64, token_hash: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE users
+                SET calendar_token_hash = ?
+                WHERE id = ?
+            "#,
+        )
+        .bind(token_hash)
+        .bind(user_id as i64)
+        .execute(&self.pool)
+        .await?;
+        println!(
+            "{} - Database - replaced calendar token of user with id {}",
+            Local::now(),
+            user_id
+        );
+
+        Ok(())
+    }
+
+    async fn get_api_tokens(&self, user_id: u64) -> anyhow::Result<Vec<models::ApiToken>> {
+        let tokens = sqlx::query_as::<_, models::ApiToken>(
+            r#"
+                SELECT id, user_id, name, scopes, created_at, last_used FROM api_tokens
+                WHERE user_id = ?
+                ORDER BY id
+            "#,
+        )
+        .bind(user_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    async fn add_api_token(
+        &self,
+        user_id: u64,
+        name: &str,
+        token_hash: &str,
+        scopes: &str,
+    ) -> anyhow::Result<()> {
+        let created_at = Utc::now().naive_utc();
+        sqlx::query(
+            r#"
+                INSERT INTO api_tokens ( user_id, name, token_hash, scopes, created_at )
+                VALUES ( ?, ?, ?, ?, ? )
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(name)
+        .bind(token_hash)
+        .bind(scopes)
+        .bind(created_at)
+        .execute(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - added api token {} for user with id {} ({})",
+            Local::now(),
+            name,
+            user_id,
+            scopes
+        );
+
+        Ok(())
+    }
+
+    async fn delete_api_token(&self, user_id: u64, id: u64) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+                DELETE FROM api_tokens
+                WHERE id = ? AND user_id = ?
+            "#,
+        )
+        .bind(id as i64)
+        .bind(user_id as i64)
+        .execute(&self.pool)
+        .await?;
+
+        println!(
+            "{} - Database - revoked api token with id {}",
+            Local::now(),
+            id
+        );
+
+        Ok(())
+    }
+
+    async fn use_api_token(&self, token_hash: &str) -> anyhow::Result<Option<models::ApiToken>> {
+        let token = sqlx::query_as::<_, models::ApiToken>(
+            r#"
+                SELECT id, user_id, name, scopes, created_at, last_used FROM api_tokens
+                WHERE token_hash = ?
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(token) = &token {
+            let now = Utc::now().naive_utc();
+            sqlx::query(
+                r#"
+                    UPDATE api_tokens
+                    SET last_used = ?
+                    WHERE id = ?
+                "#,
+            )
+            .bind(now)
+            .bind(token.id as i64)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(token)
+    }
+}
@@ -1,28 +1,152 @@
+use askama::Template;
+use axum::extract::Request;
 use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::Html;
 use axum::response::IntoResponse;
 use axum::response::Response;
+use chrono::Local;
 
-// Make our own error that wraps `anyhow::Error`.
-pub struct AppError(anyhow::Error);
+use crate::db;
+
+// Errors of a request, each is shown to the user with its own status code.
+#[derive(Debug)]
+pub enum AppError {
+    NotFound,
+    // invalid user input, the message says what to fix
+    Validation(String),
+    // the data changed since it was loaded
+    Conflict(String),
+    // uploaded backup file could not be restored
+    BadBackup(String),
+    // details are only logged, they can contain queries and connection settings
+    Database(anyhow::Error),
+    Internal(anyhow::Error),
+}
+
+impl AppError {
+    fn status(&self) -> StatusCode {
+        match self {
+            AppError::NotFound => StatusCode::NOT_FOUND,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::BadBackup(_) => StatusCode::BAD_REQUEST,
+            AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            AppError::NotFound => "The page or project does not exist".to_string(),
+            AppError::Validation(message) | AppError::Conflict(message) => message.clone(),
+            AppError::BadBackup(message) => {
+                format!("The backup could not be restored: {}", message)
+            }
+            AppError::Database(_) => {
+                "The database could not be reached, try again later".to_string()
+            }
+            AppError::Internal(_) => "Something went wrong".to_string(),
+        }
+    }
+}
+
+// status and message of an error response, rendered as page or fragment by `render_errors`
+#[derive(Clone, Debug)]
+pub struct ErrorMessage {
+    pub status: StatusCode,
+    pub message: String,
+}
 
 // Tell axum how to convert `AppError` into a response.
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Something went wrong: {}", self.0),
-        )
-            .into_response()
+        if let AppError::Database(err) | AppError::Internal(err) = &self {
+            println!("{} - Error - {:#}", Local::now(), err);
+        }
+        let error = ErrorMessage {
+            status: self.status(),
+            message: self.message(),
+        };
+        let mut response = (error.status, error.message.clone()).into_response();
+        response.extensions_mut().insert(error);
+        response
     }
 }
 
 // This enables using `?` on functions that return `Result<_, anyhow::Error>` to turn them into
-// `Result<_, AppError>`. That way you don't need to do that manually.
+// `Result<_, AppError>`. Version conflicts and database errors keep their own kind.
 impl<E> From<E> for AppError
 where
     E: Into<anyhow::Error>,
 {
     fn from(err: E) -> Self {
-        Self(err.into())
+        let err = err.into();
+        if let Some(conflict) = err.downcast_ref::<db::VersionConflict>() {
+            AppError::Conflict(conflict.to_string())
+        } else if err.chain().any(|cause| cause.is::<sqlx::Error>()) {
+            AppError::Database(err)
+        } else {
+            AppError::Internal(err)
+        }
+    }
+}
+
+#[derive(Template, Debug)]
+#[template(path = "error.html")]
+struct ErrorTemplate {
+    status: u16,
+    reason: &'static str,
+    message: String,
+}
+
+#[derive(Template, Debug)]
+#[template(path = "error_fragment.html")]
+struct ErrorFragmentTemplate {
+    message: String,
+}
+
+// middleware that renders error responses as a page, or as a fragment for htmx requests
+pub async fn render_errors(request: Request, next: Next) -> Response {
+    let htmx = request.headers().contains_key("HX-Request");
+    // forms can show their errors inline by naming an element in the X-Error-Target header
+    let target = request
+        .headers()
+        .get("X-Error-Target")
+        .and_then(|value| value.to_str().ok())
+        .filter(|target| target.starts_with('#'))
+        .unwrap_or("#errors")
+        .to_string();
+    let response = next.run(request).await;
+    let Some(error) = response.extensions().get::<ErrorMessage>().cloned() else {
+        return response;
+    };
+
+    let html = if htmx {
+        ErrorFragmentTemplate {
+            message: error.message,
+        }
+        .render()
+    } else {
+        ErrorTemplate {
+            status: error.status.as_u16(),
+            reason: error.status.canonical_reason().unwrap_or("Error"),
+            message: error.message,
+        }
+        .render()
+    };
+    let Ok(html) = html else {
+        return response;
+    };
+
+    // fragments replace the error area instead of the element that made the request
+    if htmx {
+        (
+            error.status,
+            [("HX-Retarget", target.as_str()), ("HX-Reswap", "innerHTML")],
+            Html(html),
+        )
+            .into_response()
+    } else {
+        (error.status, Html(html)).into_response()
     }
 }
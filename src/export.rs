@@ -0,0 +1,289 @@
+use serde::Deserialize;
+
+use crate::models;
+
+pub const CSV_COLUMNS: [&str; 9] = [
+    "id",
+    "name",
+    "category",
+    "position",
+    "status",
+    "notes",
+    "creation_date",
+    "start_date",
+    "completion_date",
+];
+
+fn csv_field(project: &models::Project, column: &str) -> String {
+    match column {
+        "id" => project.id.to_string(),
+        "name" => project.name.clone(),
+        "category" => project.category.clone(),
+        "position" => project.position.to_string(),
+        "status" => project.status.to_string(),
+        "notes" => project.notes.clone(),
+        "creation_date" => project.creation_date.to_string(),
+        "start_date" => project.start_date.to_string(),
+        "completion_date" => project.completion_date.to_string(),
+        _ => String::new(),
+    }
+}
+
+// write projects as RFC 4180 CSV with a header row, unknown columns are skipped
+pub fn projects_to_csv(projects: &[models::Project], columns: &[&str]) -> anyhow::Result<String> {
+    let columns: Vec<&str> = columns
+        .iter()
+        .copied()
+        .filter(|column| CSV_COLUMNS.contains(column))
+        .collect();
+
+    let mut writer = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::CRLF)
+        .from_writer(Vec::new());
+    writer.write_record(&columns)?;
+    for project in projects {
+        writer.write_record(columns.iter().map(|column| csv_field(project, column)))?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+#[derive(Debug)]
+pub struct CategoryExport {
+    pub name: String,
+    pub active: Vec<models::Project>,
+    pub completed: Vec<models::Project>,
+}
+
+// group projects by category, categories in the given order first, active projects by position
+// and completed projects by completion date
+pub fn group_by_category(
+    projects: Vec<models::Project>,
+    categories: &[&str],
+) -> Vec<CategoryExport> {
+    let mut groups: Vec<CategoryExport> = categories
+        .iter()
+        .map(|name| CategoryExport {
+            name: name.to_string(),
+            active: Vec::new(),
+            completed: Vec::new(),
+        })
+        .collect();
+
+    for project in projects {
+        let index = match groups
+            .iter()
+            .position(|group| group.name == project.category)
+        {
+            Some(index) => index,
+            None => {
+                groups.push(CategoryExport {
+                    name: project.category.clone(),
+                    active: Vec::new(),
+                    completed: Vec::new(),
+                });
+                groups.len() - 1
+            }
+        };
+        if project.status == 2 {
+            groups[index].completed.push(project);
+        } else {
+            groups[index].active.push(project);
+        }
+    }
+
+    for group in &mut groups {
+        group.active.sort_by_key(|project| project.position);
+        group.completed.sort_by(|a, b| {
+            b.completion_date
+                .cmp(&a.completion_date)
+                .then(b.id.cmp(&a.id))
+        });
+    }
+    groups
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoTag {
+    #[default]
+    Project,
+    Context,
+}
+
+// todo.txt tags cannot contain whitespace
+fn todo_tag(category: &str, tag: TodoTag) -> String {
+    let prefix = match tag {
+        TodoTag::Project => '+',
+        TodoTag::Context => '@',
+    };
+    format!(
+        "{}{}",
+        prefix,
+        category.split_whitespace().collect::<Vec<_>>().join("_")
+    )
+}
+
+// write projects in todo.txt format, one task per line
+pub fn projects_to_todo_txt(groups: &[CategoryExport], tag: TodoTag) -> String {
+    let mut lines = Vec::new();
+    for group in groups {
+        let tag = todo_tag(&group.name, tag);
+        for project in &group.active {
+            let name = project
+                .name
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            if project.status == 1 {
+                lines.push(format!(
+                    "{} {} {} start:{}",
+                    project.creation_date, name, tag, project.start_date
+                ));
+            } else {
+                lines.push(format!("{} {} {}", project.creation_date, name, tag));
+            }
+        }
+        for project in &group.completed {
+            let name = project
+                .name
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            lines.push(format!(
+                "x {} {} {} {}",
+                project.completion_date, project.creation_date, name, tag
+            ));
+        }
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use models::Project;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    #[test]
+    fn csv_quotes_fields_and_ends_lines_with_crlf() {
+        let project = Project {
+            notes: "first line\nsays \"hi\"".to_string(),
+            ..Project::example(7, "Paint, sand", "Home", date(1))
+        };
+        let csv = projects_to_csv(&[project], &["id", "name", "notes", "start_date"]).unwrap();
+        assert_eq!(
+            csv,
+            "id,name,notes,start_date\r\n7,\"Paint, sand\",\"first line\nsays \"\"hi\"\"\",2026-03-01\r\n"
+        );
+    }
+
+    #[test]
+    fn csv_skips_unknown_columns() {
+        let project = Project::example(1, "A", "Work", date(1));
+        let csv = projects_to_csv(&[project], &["name", "owner_id", "password", "status"]).unwrap();
+        assert_eq!(csv, "name,status\r\nA,0\r\n");
+    }
+
+    #[test]
+    fn csv_without_projects_has_only_the_header() {
+        assert_eq!(
+            projects_to_csv(&[], &CSV_COLUMNS).unwrap(),
+            format!("{}\r\n", CSV_COLUMNS.join(","))
+        );
+    }
+
+    #[test]
+    fn groups_follow_category_order_then_first_appearance() {
+        let projects = vec![
+            Project::example(1, "A", "Other", date(1)),
+            Project::example(2, "B", "Work", date(1)),
+            Project::example(3, "C", "Later", date(1)),
+        ];
+        let groups = group_by_category(projects, &["Home", "Work"]);
+        let names: Vec<_> = groups.iter().map(|group| group.name.as_str()).collect();
+        assert_eq!(names, ["Home", "Work", "Other", "Later"]);
+        assert!(groups[0].active.is_empty());
+    }
+
+    #[test]
+    fn groups_sort_active_by_position_and_completed_newest_first() {
+        let projects = vec![
+            Project {
+                position: 2,
+                ..Project::example(1, "Second", "Work", date(1))
+            },
+            Project {
+                position: 1,
+                ..Project::example(2, "First", "Work", date(1))
+            },
+            Project {
+                status: 2,
+                completion_date: date(3),
+                ..Project::example(3, "Older", "Work", date(1))
+            },
+            Project {
+                status: 2,
+                completion_date: date(5),
+                ..Project::example(4, "Newer", "Work", date(1))
+            },
+            Project {
+                status: 2,
+                completion_date: date(5),
+                ..Project::example(5, "Newest id", "Work", date(1))
+            },
+        ];
+        let groups = group_by_category(projects, &["Work"]);
+        let names = |projects: &[Project]| {
+            projects
+                .iter()
+                .map(|project| project.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&groups[0].active), ["First", "Second"]);
+        assert_eq!(names(&groups[0].completed), ["Newest id", "Newer", "Older"]);
+    }
+
+    #[test]
+    fn todo_tags_replace_whitespace() {
+        assert_eq!(
+            todo_tag("Side  projects 2026", TodoTag::Project),
+            "+Side_projects_2026"
+        );
+        assert_eq!(todo_tag("Home", TodoTag::Context), "@Home");
+    }
+
+    #[test]
+    fn todo_txt_marks_started_and_completed_projects() {
+        let projects = vec![
+            Project::example(1, "Plan  trip", "Travel plans", date(1)),
+            Project {
+                status: 1,
+                start_date: date(4),
+                ..Project::example(2, "Book\nhotel", "Travel plans", date(2))
+            },
+            Project {
+                status: 2,
+                completion_date: date(9),
+                ..Project::example(3, "Renew passport", "Travel plans", date(3))
+            },
+        ];
+        let groups = group_by_category(projects, &[]);
+        assert_eq!(
+            projects_to_todo_txt(&groups, TodoTag::Project),
+            "2026-03-01 Plan trip +Travel_plans\n\
+             2026-03-02 Book hotel +Travel_plans start:2026-03-04\n\
+             x 2026-03-09 2026-03-03 Renew passport +Travel_plans\n"
+        );
+    }
+
+    #[test]
+    fn todo_txt_without_projects_is_empty() {
+        assert_eq!(projects_to_todo_txt(&[], TodoTag::Context), "");
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{NaiveDateTime, Utc};
+
+use crate::models;
+use crate::urls;
+
+// number of entries per feed
+pub const FEED_LENGTH: u64 = 50;
+
+#[derive(Debug)]
+pub struct FeedEntry {
+    pub id: String,
+    pub title: String,
+    pub updated: String,
+    pub category: String,
+    pub content: String,
+    pub link: String,
+}
+
+#[derive(Debug)]
+pub struct Feed {
+    pub id: String,
+    pub title: String,
+    pub updated: String,
+    pub link: String,
+    pub entries: Vec<FeedEntry>,
+}
+
+// dates and times are stored without timezone, activity times are utc
+fn timestamp(date: NaiveDateTime) -> String {
+    date.and_utc().to_rfc3339()
+}
+
+// newest entry, or now for an empty feed
+fn updated(entries: &[FeedEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| entry.updated.clone())
+        .max()
+        .unwrap_or_else(|| Utc::now().to_rfc3339())
+}
+
+pub fn completed_feed(projects: &[models::Project]) -> Feed {
+    let entries: Vec<FeedEntry> = projects
+        .iter()
+        .map(|project| FeedEntry {
+            id: format!("tag:project-tracker,2024:project/{}", project.id),
+            title: format!("Completed: {}", project.name),
+            updated: timestamp(project.completion_date.and_hms_opt(0, 0, 0).unwrap()),
+            category: project.category.clone(),
+            content: project.notes.clone(),
+            link: urls::url(&format!("/{}", project.id)),
+        })
+        .collect();
+
+    Feed {
+        id: "tag:project-tracker,2024:completed".to_string(),
+        title: "Completed Projects".to_string(),
+        updated: updated(&entries),
+        link: urls::url("/feed/completed.atom"),
+        entries,
+    }
+}
+
+pub fn activity_feed(activity: &[models::Activity]) -> Feed {
+    let entries: Vec<FeedEntry> = activity
+        .iter()
+        .map(|event| FeedEntry {
+            id: format!("tag:project-tracker,2024:activity/{}", event.id),
+            title: format!("{}: {}", event.project_name, event.event),
+            updated: timestamp(event.created_at),
+            category: event.category.clone(),
+            content: format!("{} {}", event.project_name, event.event),
+            link: urls::url(&format!("/{}", event.project_id)),
+        })
+        .collect();
+
+    Feed {
+        id: "tag:project-tracker,2024:activity".to_string(),
+        title: "Project Activity".to_string(),
+        updated: updated(&entries),
+        link: urls::url("/feed/activity.atom"),
+        entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn activity(id: u64, event: &str, created_at: NaiveDateTime) -> models::Activity {
+        models::Activity {
+            id,
+            project_id: 1,
+            project_name: "Report".to_string(),
+            category: "Work".to_string(),
+            event: event.to_string(),
+            created_at,
+        }
+    }
+
+    #[test]
+    fn entries_keep_their_order_and_the_newest_one_dates_the_feed() {
+        let time = |day, hour| {
+            NaiveDate::from_ymd_opt(2026, 3, day)
+                .unwrap()
+                .and_hms_opt(hour, 30, 0)
+                .unwrap()
+        };
+        let feed = activity_feed(&[
+            activity(3, "completed", time(5, 9)),
+            activity(2, "started", time(2, 14)),
+            activity(4, "notes updated", time(5, 17)),
+        ]);
+        let ids: Vec<_> = feed.entries.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "tag:project-tracker,2024:activity/3",
+                "tag:project-tracker,2024:activity/2",
+                "tag:project-tracker,2024:activity/4"
+            ]
+        );
+        assert_eq!(feed.entries[0].title, "Report: completed");
+        assert_eq!(feed.entries[1].updated, "2026-03-02T14:30:00+00:00");
+        assert_eq!(feed.updated, "2026-03-05T17:30:00+00:00");
+    }
+
+    #[test]
+    fn completed_projects_are_dated_by_their_completion_day() {
+        let start = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let mut project = models::Project::example(7, "Report", "Work", start);
+        project.completion_date = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+        let feed = completed_feed(&[project]);
+        assert_eq!(feed.entries[0].title, "Completed: Report");
+        assert_eq!(feed.entries[0].updated, "2026-03-09T00:00:00+00:00");
+        assert_eq!(feed.entries[0].link, urls::url("/7"));
+        assert_eq!(feed.updated, feed.entries[0].updated);
+    }
+
+    #[test]
+    fn empty_feed_is_dated_now() {
+        let before = Utc::now().to_rfc3339();
+        let feed = completed_feed(&[]);
+        assert!(feed.entries.is_empty());
+        assert!(feed.updated[..19] >= before[..19]);
+    }
+}
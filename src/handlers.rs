@@ -1,117 +1,404 @@
 use askama::Template;
+use axum::body::Body;
+use axum::extract::multipart::MultipartError;
+use axum::extract::Extension;
 use axum::extract::Form;
 use axum::extract::Multipart;
 use axum::extract::Path;
 use axum::extract::Query;
+use axum::extract::RawQuery;
 use axum::extract::State;
-use axum::body::Body;
+use axum::http::header;
+use axum::http::HeaderMap;
 use axum::http::Response;
 use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::Html;
 use axum::response::IntoResponse;
+use axum::response::Json;
 use axum::response::Redirect;
-use chrono::Local;
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
 use serde::Deserialize;
-use sqlx::mysql::MySqlPool;
+use std::convert::Infallible;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
 
+use crate::assets;
+use crate::auth;
+use crate::config;
+use crate::csrf;
 use crate::db;
 use crate::error;
+use crate::export;
+use crate::feed;
+use crate::ical;
+use crate::import;
 use crate::models;
-
-const LEFT_CATEGORY: &str = "Personal";
-const RIGHT_CATEGORY: &str = "Professional";
+use crate::report;
+use crate::state::AppState;
+use crate::timeline;
+use crate::urls;
+use crate::validate;
+use crate::webhooks;
 
 #[derive(Template, Debug)]
 #[template(path = "index.html")]
 pub struct IndexTemplate {
-    pub projects: Vec<models::Project>,
-    pub left_category: &'static str,
-    pub right_category: &'static str,
+    pub csrf_token: csrf::CsrfToken,
+    pub lists: Vec<CategoryList>,
+    pub conflict: bool,
+    pub username: String,
+    pub features: config::Features,
 }
 
 #[derive(Template, Debug)]
 #[template(path = "completed.html")]
 pub struct CompletedTemplate {
-    pub projects: Vec<models::Project>,
     pub block: Vec<models::Project>,
-    pub left_category: &'static str,
-    pub right_category: &'static str,
+    pub categories: Vec<String>,
+    pub total: u64,
     pub next_block: u64,
-    pub more: u8,
+    pub has_more: bool,
+    pub features: config::Features,
 }
 
 #[derive(Template, Debug)]
 #[template(path = "block.html")]
 pub struct BlockTemplate {
     pub block: Vec<models::Project>,
-    pub left_category: &'static str,
-    pub right_category: &'static str,
     pub next_block: u64,
-    pub more: u8,
+    pub has_more: bool,
+}
+
+// project list of one category, own or shared by another user
+#[derive(Debug)]
+pub struct CategoryList {
+    pub category: models::Category,
+    // name of the owner for shared categories
+    pub owner: Option<String>,
+    pub editable: bool,
+    pub projects: Vec<models::Project>,
 }
 
 #[derive(Template, Debug)]
 #[template(path = "list.html")]
-struct ProjectListTemplate<'a> {
-    projects: Vec<models::Project>,
-    category_name: &'a str,
+struct ProjectListTemplate {
+    list: CategoryList,
+    // the list was reloaded because it changed in another tab
+    conflict: bool,
 }
 
 #[derive(Template, Debug)]
 #[template(path = "project.html")]
 pub struct ProjectTemplate {
+    pub csrf_token: csrf::CsrfToken,
     pub project: models::Project,
+    pub editable: bool,
+    // validation error of the notes form
+    pub error: Option<String>,
+}
+
+// shown instead of overwriting a project that changed since the form was loaded
+#[derive(Template, Debug)]
+#[template(path = "conflict.html")]
+pub struct ConflictTemplate {
+    pub csrf_token: csrf::CsrfToken,
+    // current version, none if the project was deleted
+    pub project: Option<models::Project>,
+    pub action: &'static str,
+    // submitted notes, if notes were edited
+    pub notes: Option<String>,
+}
+
+// validation messages start lowercase so they can follow a row number, shown alone they start uppercase
+fn sentence(message: String) -> String {
+    let mut chars = message.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => message,
+    }
+}
+
+fn invalid(message: String) -> error::AppError {
+    error::AppError::Validation(sentence(message))
+}
+
+// split version conflicts from other database errors
+fn is_conflict(result: anyhow::Result<()>) -> Result<bool, error::AppError> {
+    match result {
+        Ok(()) => Ok(false),
+        Err(err) if err.is::<db::VersionConflict>() => Ok(true),
+        Err(err) => Err(err.into()),
+    }
+}
+
+// names of the categories of a user, in the order they were created
+async fn category_names(db: &db::Db, user: &models::User) -> anyhow::Result<Vec<String>> {
+    let categories = db.get_categories(user.id).await?;
+    Ok(categories
+        .into_iter()
+        .map(|category| category.name)
+        .collect())
+}
+
+// project list of a category, if the user may see it
+async fn load_list(
+    db: &db::Db,
+    user: &models::User,
+    category: models::Category,
+) -> anyhow::Result<Option<CategoryList>> {
+    let Some(access) = db
+        .get_access(user.id, category.owner_id, &category.name)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let owner = if category.owner_id == user.id {
+        None
+    } else {
+        db.get_user(category.owner_id)
+            .await?
+            .map(|owner| owner.username)
+    };
+    Ok(Some(CategoryList {
+        projects: db
+            .get_category_projects(category.owner_id, &category.name)
+            .await?,
+        owner,
+        editable: access == models::Access::Edit,
+        category,
+    }))
+}
+
+// project list of the category a project is in
+async fn load_project_list(
+    db: &db::Db,
+    user: &models::User,
+    project: &models::Project,
+    conflict: bool,
+) -> Result<Response<Body>, error::AppError> {
+    let category = db
+        .get_category_by_name(project.owner_id, &project.category)
+        .await?;
+    let Some(list) = (match category {
+        Some(category) => load_list(db, user, category).await?,
+        None => None,
+    }) else {
+        return Err(error::AppError::NotFound);
+    };
+    let html = ProjectListTemplate { list, conflict }.render()?;
+    Ok(Html(html).into_response())
+}
+
+// project with id if the user may see it, with whether they may change it
+async fn load_project(
+    db: &db::Db,
+    user: &models::User,
+    id: u64,
+) -> anyhow::Result<Option<(models::Project, bool)>> {
+    let Some(project) = db.get_project(id).await? else {
+        return Ok(None);
+    };
+    let access = db
+        .get_access(user.id, project.owner_id, &project.category)
+        .await?;
+    Ok(access.map(|access| (project, access == models::Access::Edit)))
+}
+
+// project with id if the user may change it
+async fn editable_project(
+    db: &db::Db,
+    user: &models::User,
+    id: u64,
+) -> anyhow::Result<Option<models::Project>> {
+    let project = load_project(db, user, id).await?;
+    Ok(project
+        .filter(|(_, editable)| *editable)
+        .map(|(project, _)| project))
+}
+
+// FALLBACK HANDLER
+pub async fn fallback_handler() -> error::AppError {
+    error::AppError::NotFound
+}
+
+// ASSET HANDLER
+#[axum_macros::debug_handler]
+pub async fn asset_handler(
+    Path(path): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, error::AppError> {
+    let Some(asset) = assets::get(&path) else {
+        return Err(error::AppError::NotFound);
+    };
+    // browsers revalidating a cached file only need to hear it is unchanged
+    let not_modified = headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| assets::etag_matches(value, asset.etag));
+    let status = if not_modified {
+        StatusCode::NOT_MODIFIED
+    } else {
+        StatusCode::OK
+    };
+    let body = if status == StatusCode::OK {
+        Body::from(asset.body)
+    } else {
+        Body::empty()
+    };
+    let response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, assets::content_type(asset.path))
+        .header(header::ETAG, asset.etag)
+        .header(header::CACHE_CONTROL, assets::CACHE_CONTROL)
+        .body(body)
+        .unwrap();
+    Ok(response)
 }
 
 // INDEX HANDLER
 #[axum_macros::debug_handler]
 pub async fn index_handler(
-    State(pool): State<MySqlPool>,
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
 ) -> Result<IndexTemplate, error::AppError> {
-    let project_list = db::get_projects(&pool).await?;
+    // own categories first, then the ones shared by others
+    let mut categories = state.db.get_categories(user.id).await?;
+    categories.extend(state.db.get_shared_categories(user.id).await?);
+    let mut lists = Vec::new();
+    for category in categories {
+        lists.extend(load_list(&state.db, &user, category).await?);
+    }
     return Ok(IndexTemplate {
-        projects: project_list,
-        left_category: LEFT_CATEGORY,
-        right_category: RIGHT_CATEGORY,
+        lists,
+        conflict: false,
+        username: user.username,
+        features: state.features,
+        csrf_token,
+    });
+}
+
+// LIST HANDLER
+#[axum_macros::debug_handler]
+pub async fn list_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Path(category_id): Path<u64>,
+) -> Result<Response<Body>, error::AppError> {
+    let list = match db.get_category(category_id).await? {
+        Some(category) => load_list(&db, &user, category).await?,
+        None => None,
+    };
+    let Some(list) = list else {
+        return Err(error::AppError::NotFound);
+    };
+    let html = ProjectListTemplate {
+        list,
+        conflict: false,
+    }
+    .render()?;
+    Ok(Html(html).into_response())
+}
+
+// EVENTS HANDLER
+#[axum_macros::debug_handler]
+pub async fn events_handler(
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, error::AppError> {
+    // only changes of the lists the page shows, categories added or shared later appear after a reload
+    let mut visible = state.db.get_categories(user.id).await?;
+    visible.extend(state.db.get_shared_categories(user.id).await?);
+    let visible: Vec<u64> = visible.into_iter().map(|category| category.id).collect();
+
+    // one event per changed category, named after the list it refreshes
+    // lagging pages skip missed changes, the next change reloads the list again
+    let stream = BroadcastStream::new(state.changes.subscribe()).filter_map(move |category_id| {
+        category_id
+            .ok()
+            .filter(|category_id| visible.contains(category_id))
+            .map(|category_id| {
+                Ok(Event::default()
+                    .event(format!("changed-{}", category_id))
+                    .data(category_id.to_string()))
+            })
     });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
 }
 
 // START HANDLER
 #[derive(Deserialize, Debug)]
 pub struct StartQuery {
     pub id: u64,
-    pub category: String,
-    pub position: u64,
+    pub version: u64,
 }
 
 #[axum_macros::debug_handler]
 pub async fn start_handler(
-    State(pool): State<MySqlPool>,
-    Form(query): Form<DeleteQuery>,
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    Form(query): Form<StartQuery>,
 ) -> Result<impl IntoResponse, error::AppError> {
-    db::start_project(&pool, query.id, query.category.clone(), query.position).await?;
-    let projects = db::get_projects(&pool).await?;
-    let context = ProjectListTemplate {
-        projects,
-        category_name: &query.category,
+    let Some(project) = editable_project(&state.db, &user, query.id).await? else {
+        return Err(error::AppError::NotFound);
     };
-    let html = context.render()?;
-    Ok(Html(html))
+    let conflict = is_conflict(
+        state
+            .db
+            .start_project(
+                project.id,
+                project.owner_id,
+                project.category.clone(),
+                project.position,
+                query.version,
+            )
+            .await,
+    )?;
+    if !conflict {
+        if let Some(project) = state.db.get_project(query.id).await? {
+            state.publish("project.started", project).await;
+        }
+    }
+    load_project_list(&state.db, &user, &project, conflict).await
 }
 
 // COMPLETE HANDLER
 #[derive(Deserialize, Debug)]
 pub struct CompleteQuery {
     pub id: u64,
+    pub version: u64,
 }
 
 #[axum_macros::debug_handler]
 pub async fn complete_handler(
-    State(pool): State<MySqlPool>,
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
     Form(query): Form<CompleteQuery>,
 ) -> Result<impl IntoResponse, error::AppError> {
-    db::complete_project(&pool, query.id).await?;
-    Ok(Redirect::to("/completed?block=1"))
+    if editable_project(&state.db, &user, query.id)
+        .await?
+        .is_none()
+    {
+        return Err(error::AppError::NotFound);
+    }
+    let conflict = is_conflict(state.db.complete_project(query.id, query.version).await)?;
+    let project = state.db.get_project(query.id).await?;
+    if conflict {
+        let context = ConflictTemplate {
+            csrf_token,
+            project,
+            action: "complete",
+            notes: None,
+        };
+        return Ok(Html(context.render()?).into_response());
+    }
+    if let Some(project) = project {
+        state.publish("project.completed", project).await;
+    }
+    Ok(Redirect::to(&urls::url("/completed?block=1")).into_response())
 }
 
 // COMPLETED HANDLER
@@ -122,127 +409,466 @@ pub struct BlockQuery {
 
 #[axum_macros::debug_handler]
 pub async fn completed_handler(
-    State(pool): State<MySqlPool>,
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
     Query(query): Query<BlockQuery>,
 ) -> Result<impl IntoResponse, error::AppError> {
+    let page = state
+        .db
+        .get_completed_projects(user.id, query.block, state.page_size)
+        .await?;
+
     // load completed projects html with timeline
     if query.block == 1 {
-        let all_completed = db::get_completed_projects(&pool, 0).await?;
-        let completed_block = db::get_completed_projects(&pool, 1).await?;
-        let mut more_blocks = 1;
-
-        if completed_block.len() < 10 {
-            more_blocks = 0;
-        }
         let context = CompletedTemplate {
-            projects: all_completed,
-            block: completed_block,
-            left_category: LEFT_CATEGORY,
-            right_category: RIGHT_CATEGORY,
+            block: page.projects,
+            categories: category_names(&state.db, &user).await?,
+            total: page.total,
             next_block: query.block + 1,
-            more: more_blocks,
+            has_more: page.has_more,
+            features: state.features,
         };
         let html = context.render()?;
         Ok(Html(html))
 
         // load blocks of completed projects
     } else {
-        let completed_block = db::get_completed_projects(&pool, query.block).await?;
-        let mut more_blocks = 1;
-
-        if completed_block.len() < 10 {
-            more_blocks = 0;
-        }
-
         let context = BlockTemplate {
-            block: completed_block,
-            left_category: LEFT_CATEGORY,
-            right_category: RIGHT_CATEGORY,
+            block: page.projects,
             next_block: query.block + 1,
-            more: more_blocks,
+            has_more: page.has_more,
         };
         let html = context.render()?;
         Ok(Html(html))
     }
 }
 
+// TIMELINE HANDLER
+#[derive(Deserialize, Debug)]
+pub struct TimelineQuery {
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    #[serde(default)]
+    pub granularity: timeline::Granularity,
+    pub in_progress: Option<bool>,
+    #[serde(default)]
+    pub download: bool,
+}
+
+// values of a repeated query parameter, e.g. category=Home&category=Work,
+// the query extractor only reads single values
+fn query_values(raw_query: Option<&str>, key: &str) -> Vec<String> {
+    serde_urlencoded::from_str::<Vec<(String, String)>>(raw_query.unwrap_or_default())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(name, _)| name == key)
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+// load timeline for the requested range, defaulting to the last year
+// projects of the given category names, none for all categories
+async fn load_timeline(
+    db: &db::Db,
+    user: &models::User,
+    query: &TimelineQuery,
+    categories: &[String],
+    in_progress: bool,
+) -> anyhow::Result<Option<timeline::Timeline>> {
+    let today = Local::now().date_naive();
+    let end = query.end.unwrap_or(today);
+    let start = query.start.unwrap_or(end - Days::new(365));
+    if start > end {
+        return Ok(None);
+    }
+
+    let projects = db
+        .get_timeline_projects(user.id, start, end, categories, in_progress)
+        .await?;
+    Ok(Some(timeline::build(
+        projects,
+        start,
+        end,
+        query.granularity,
+        today,
+    )))
+}
+
+#[axum_macros::debug_handler]
+pub async fn timeline_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Query(query): Query<TimelineQuery>,
+    RawQuery(raw_query): RawQuery,
+) -> Result<Response<Body>, error::AppError> {
+    let categories = query_values(raw_query.as_deref(), "category");
+    match load_timeline(
+        &db,
+        &user,
+        &query,
+        &categories,
+        query.in_progress.unwrap_or(false),
+    )
+    .await?
+    {
+        Some(timeline) => Ok(Json(timeline).into_response()),
+        None => Err(error::AppError::Validation(
+            "Timeline start must not be after end".to_string(),
+        )),
+    }
+}
+
+// TIMELINE SVG HANDLER
+#[derive(Template, Debug)]
+#[template(path = "timeline.svg")]
+struct TimelineSvgTemplate {
+    chart: timeline::SvgChart,
+}
+
+#[axum_macros::debug_handler]
+pub async fn timeline_svg_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Query(query): Query<TimelineQuery>,
+    RawQuery(raw_query): RawQuery,
+) -> Result<Response<Body>, error::AppError> {
+    let categories = query_values(raw_query.as_deref(), "category");
+    let Some(timeline) = load_timeline(
+        &db,
+        &user,
+        &query,
+        &categories,
+        query.in_progress.unwrap_or(true),
+    )
+    .await?
+    else {
+        return Err(error::AppError::Validation(
+            "Timeline start must not be after end".to_string(),
+        ));
+    };
+
+    let categories = category_names(&db, &user).await?;
+    let categories: Vec<&str> = categories.iter().map(String::as_str).collect();
+    let context = TimelineSvgTemplate {
+        chart: timeline::svg_chart(&timeline, &categories, Local::now().date_naive()),
+    };
+    let svg = context.render()?;
+
+    let mut response = Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "image/svg+xml");
+    if query.download {
+        let filename = format!("timeline_{}_{}.svg", timeline.start, timeline.end);
+        response = response.header(
+            "Content-Disposition",
+            format!("attachment; filename={}", filename),
+        );
+    }
+    Ok(response.body(Body::from(svg))?)
+}
+
+// STATS HANDLER
+const BACKLOG_AGE_LABELS: [&str; 5] = [
+    "< 1 week",
+    "1 week - 1 month",
+    "1 - 3 months",
+    "3 - 12 months",
+    "> 1 year",
+];
+
+#[derive(Debug)]
+pub struct MonthRow {
+    pub month: String,
+    // completed projects and chart color per category, in the order of the categories
+    pub counts: Vec<(i64, &'static str)>,
+    pub total: i64,
+}
+
+#[derive(Template, Debug)]
+#[template(path = "stats.html")]
+pub struct StatsTemplate {
+    pub months: Vec<MonthRow>,
+    pub max_month: i64,
+    pub durations: models::DurationStats,
+    pub wip: Vec<models::CategoryCount>,
+    pub backlog_age: Vec<(&'static str, i64)>,
+    pub oldest: Vec<models::Project>,
+    pub categories: Vec<String>,
+}
+
+#[axum_macros::debug_handler]
+pub async fn stats_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+) -> Result<impl IntoResponse, error::AppError> {
+    let today = Local::now().date_naive();
+    let first_month = today.with_day(1).unwrap() - Months::new(11);
+    let categories = category_names(&db, &user).await?;
+
+    // one row per month of the last year, including months without completions
+    let completed = db.get_completed_per_month(user.id, first_month).await?;
+    let months: Vec<MonthRow> = (0..12)
+        .map(|offset| {
+            let month = (today.with_day(1).unwrap() - Months::new(offset))
+                .format("%Y-%m")
+                .to_string();
+            let count = |category: &str| {
+                completed
+                    .iter()
+                    .filter(|row| {
+                        row.month.as_deref() == Some(month.as_str()) && row.category == category
+                    })
+                    .map(|row| row.completed)
+                    .sum()
+            };
+            let counts: Vec<(i64, &'static str)> = categories
+                .iter()
+                .enumerate()
+                .map(|(index, category)| {
+                    (
+                        count(category),
+                        timeline::SVG_COLORS[index % timeline::SVG_COLORS.len()],
+                    )
+                })
+                .collect();
+            MonthRow {
+                total: counts.iter().map(|(count, _)| count).sum(),
+                counts,
+                month,
+            }
+        })
+        .collect();
+
+    let cutoffs = [
+        today - Days::new(7),
+        today - Days::new(30),
+        today - Days::new(90),
+        today - Days::new(365),
+    ];
+    let buckets = db.get_backlog_age(user.id, cutoffs).await?;
+    let backlog_age = BACKLOG_AGE_LABELS
+        .iter()
+        .enumerate()
+        .map(|(index, label)| {
+            let total = buckets
+                .iter()
+                .find(|bucket| bucket.bucket == index as i64)
+                .map_or(0, |bucket| bucket.total);
+            (*label, total)
+        })
+        .collect();
+
+    let context = StatsTemplate {
+        max_month: months.iter().map(|row| row.total).max().unwrap_or(0).max(1),
+        months,
+        durations: db.get_duration_stats(user.id).await?,
+        wip: db.get_work_in_progress(user.id).await?,
+        backlog_age,
+        oldest: db.get_oldest_backlog(user.id, 10).await?,
+        categories,
+    };
+    let html = context.render()?;
+    Ok(Html(html))
+}
+
+// REPORT HANDLER
+#[derive(Deserialize, Debug)]
+pub struct ReportQuery {
+    pub year: Option<i32>,
+    pub format: Option<String>,
+}
+
+#[derive(Template, Debug)]
+#[template(path = "report.html")]
+pub struct ReportTemplate {
+    pub report: report::YearReport,
+}
+
+#[derive(Template, Debug)]
+#[template(path = "report.md")]
+pub struct ReportMarkdownTemplate {
+    pub report: report::YearReport,
+}
+
+#[axum_macros::debug_handler]
+pub async fn report_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Query(query): Query<ReportQuery>,
+) -> Result<Response<Body>, error::AppError> {
+    let year = query.year.unwrap_or_else(|| Local::now().year());
+    let projects = db.get_completed_projects_by_year(user.id, year).await?;
+    let categories = category_names(&db, &user).await?;
+    let categories: Vec<&str> = categories.iter().map(String::as_str).collect();
+    let report = report::build(year, &projects, &categories);
+
+    // return markdown file or printable html page
+    if query.format.as_deref() == Some("md") {
+        let markdown = ReportMarkdownTemplate { report }.render()?;
+        let response = Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "text/markdown; charset=utf-8")
+            .header(
+                "Content-Disposition",
+                format!("attachment; filename=year_in_review_{}.md", year),
+            )
+            .body(Body::from(markdown))?;
+        Ok(response)
+    } else {
+        let html = ReportTemplate { report }.render()?;
+        Ok(Html(html).into_response())
+    }
+}
+
 // ADD HANDLER
 #[derive(Deserialize, Debug)]
 pub struct AddQuery {
     pub name: String,
-    pub category: String,
+    pub category_id: u64,
 }
 
 #[axum_macros::debug_handler]
 pub async fn add_handler(
-    State(pool): State<MySqlPool>,
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
     Form(query): Form<AddQuery>,
 ) -> Result<impl IntoResponse, error::AppError> {
-    db::add_project(&pool, query.name, query.category.clone()).await?;
+    let Some(list) = (match state.db.get_category(query.category_id).await? {
+        Some(category) => load_list(&state.db, &user, category).await?,
+        None => None,
+    }) else {
+        return Err(error::AppError::NotFound);
+    };
+    if !list.editable {
+        return Err(error::AppError::NotFound);
+    }
 
-    let projects = db::get_projects(&pool).await?;
-    let context = ProjectListTemplate {
-        projects,
-        category_name: &query.category,
+    let name = validate::project_name(&query.name).map_err(invalid)?;
+
+    // new projects belong to the owner of the category, also when added by someone it is shared with
+    let category = list.category;
+    let id = state
+        .db
+        .add_project(category.owner_id, name.to_string(), category.name.clone())
+        .await?;
+    if let Some(project) = state.db.get_project(id).await? {
+        state.publish("project.added", project).await;
+    }
+
+    let Some(list) = load_list(&state.db, &user, category).await? else {
+        return Err(error::AppError::NotFound);
     };
-    let html = context.render()?;
-    Ok(Html(html))
+    let html = ProjectListTemplate {
+        list,
+        conflict: false,
+    }
+    .render()?;
+    Ok(Html(html).into_response())
 }
 
 // DELETE HANDLER
 #[derive(Deserialize, Debug)]
 pub struct DeleteQuery {
     pub id: u64,
-    pub category: String,
-    pub position: u64,
+    pub version: u64,
 }
 
 #[axum_macros::debug_handler]
 pub async fn delete_handler(
-    State(pool): State<MySqlPool>,
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
     Form(query): Form<DeleteQuery>,
 ) -> Result<impl IntoResponse, error::AppError> {
     // TODO: add delete confirmation
-    db::delete_project(&pool, query.id, query.category, query.position).await?;
-    Ok(Redirect::to("/"))
+    let Some(project) = editable_project(&state.db, &user, query.id).await? else {
+        return Err(error::AppError::NotFound);
+    };
+    let conflict = is_conflict(
+        state
+            .db
+            .delete_project(
+                project.id,
+                project.owner_id,
+                project.category.clone(),
+                project.position,
+                query.version,
+            )
+            .await,
+    )?;
+    if conflict {
+        let context = ConflictTemplate {
+            csrf_token,
+            project: Some(project),
+            action: "delete",
+            notes: None,
+        };
+        return Ok(Html(context.render()?).into_response());
+    }
+    state.publish("project.deleted", project).await;
+    Ok(Redirect::to(&urls::url("/")).into_response())
 }
 
 // MOVE HANDLER
 #[derive(Deserialize, Debug)]
 pub struct MoveQuery {
     pub id: u64,
-    pub category: String,
-    pub position: u64,
+    pub version: u64,
 }
 
 #[axum_macros::debug_handler]
 pub async fn up_handler(
-    State(pool): State<MySqlPool>,
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
     Form(query): Form<MoveQuery>,
 ) -> Result<impl IntoResponse, error::AppError> {
-    db::move_project_up(&pool, query.id, query.category.clone(), query.position).await?;
-    let projects = db::get_projects(&pool).await?;
-    let context = ProjectListTemplate {
-        projects,
-        category_name: &query.category,
+    let Some(project) = editable_project(&state.db, &user, query.id).await? else {
+        return Err(error::AppError::NotFound);
     };
-    let html = context.render()?;
-    Ok(Html(html))
+    let conflict = is_conflict(
+        state
+            .db
+            .move_project_up(
+                project.id,
+                project.owner_id,
+                project.category.clone(),
+                project.position,
+                query.version,
+            )
+            .await,
+    )?;
+    if !conflict {
+        state.notify(project.owner_id, &project.category).await;
+    }
+    load_project_list(&state.db, &user, &project, conflict).await
 }
 
 #[axum_macros::debug_handler]
 pub async fn down_handler(
-    State(pool): State<MySqlPool>,
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
     Form(query): Form<MoveQuery>,
 ) -> Result<impl IntoResponse, error::AppError> {
-    db::move_project_down(&pool, query.id, query.category.clone(), query.position).await?;
-    let projects = db::get_projects(&pool).await?;
-    let context = ProjectListTemplate {
-        projects,
-        category_name: &query.category,
+    let Some(project) = editable_project(&state.db, &user, query.id).await? else {
+        return Err(error::AppError::NotFound);
     };
-    let html = context.render()?;
-    Ok(Html(html))
+    let conflict = is_conflict(
+        state
+            .db
+            .move_project_down(
+                project.id,
+                project.owner_id,
+                project.category.clone(),
+                project.position,
+                query.version,
+            )
+            .await,
+    )?;
+    if !conflict {
+        state.notify(project.owner_id, &project.category).await;
+    }
+    load_project_list(&state.db, &user, &project, conflict).await
 }
 
 // UPDATE NOTES HANDLER
@@ -250,35 +876,93 @@ pub async fn down_handler(
 pub struct UpdateNotesQuery {
     pub id: u64,
     pub notes: String,
+    pub version: u64,
 }
 
 #[axum_macros::debug_handler]
 pub async fn update_notes_handler(
-    State(pool): State<MySqlPool>,
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
     Form(query): Form<UpdateNotesQuery>,
 ) -> Result<impl IntoResponse, error::AppError> {
-    db::update_notes(&pool, query.id, query.notes).await?;
-    Ok(Redirect::to("/"))
+    let Some(mut project) = editable_project(&db, &user, query.id).await? else {
+        return Err(error::AppError::NotFound);
+    };
+    // show the form again with the submitted notes, so nothing typed is lost
+    if let Err(message) = validate::notes(&query.notes) {
+        project.notes = query.notes;
+        let context = ProjectTemplate {
+            csrf_token,
+            project,
+            editable: true,
+            error: Some(message),
+        };
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(context.render()?)).into_response());
+    }
+    let conflict = is_conflict(
+        db.update_notes(query.id, query.notes.clone(), query.version)
+            .await,
+    )?;
+    if conflict {
+        let context = ConflictTemplate {
+            csrf_token,
+            project: db.get_project(query.id).await?,
+            action: "update notes",
+            notes: Some(query.notes),
+        };
+        return Ok(Html(context.render()?).into_response());
+    }
+    Ok(Redirect::to(&urls::url("/")).into_response())
 }
 
 // PROJECT HANDLER
 #[axum_macros::debug_handler]
 pub async fn project_handler(
-    State(pool): State<MySqlPool>,
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
     Path(id): Path<u64>,
-) -> Result<ProjectTemplate, error::AppError> {
-    let project = db::get_project(&pool, id).await?;
-    Ok(ProjectTemplate {
-        project: project.expect("Did not find project"),
-    })
+) -> Result<Response<Body>, error::AppError> {
+    let Some((project, editable)) = load_project(&db, &user, id).await? else {
+        return Err(error::AppError::NotFound);
+    };
+    let html = ProjectTemplate {
+        csrf_token,
+        project,
+        editable,
+        error: None,
+    }
+    .render()?;
+    Ok(Html(html).into_response())
 }
 
 // BACKUP HANDLER
 #[axum_macros::debug_handler]
 pub async fn backup_handler(
-    State(pool): State<MySqlPool>,
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
 ) -> Result<impl IntoResponse, error::AppError> {
-    let projects = db::get_projects(&pool).await?;
+    let json_str = backup_json(&db, &user).await?;
+    let filename = Local::now()
+        .format("backup_%Y-%m-%d_%H-%M-%S.json")
+        .to_string();
+    // return response with json file
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .header(
+            "Content-Disposition",
+            format!("attachment; filename={}", filename),
+        )
+        .body(json_str)
+        .unwrap();
+    Ok(response)
+}
+
+// all projects of a user in the backup format read by restore
+async fn backup_json(db: &db::Db, user: &models::User) -> anyhow::Result<String> {
+    let projects = db.get_projects(user.id).await?;
     // parse projects into json
     let projects_json: Vec<serde_json::Value> = projects
         .into_iter()
@@ -297,69 +981,1293 @@ pub async fn backup_handler(
         })
         .collect();
     // create json string
-    let json_str = serde_json::to_string(&projects_json).unwrap();
+    Ok(serde_json::to_string(&projects_json)?)
+}
+
+// EXPORT HANDLER
+#[derive(Template, Debug)]
+#[template(path = "export.html")]
+struct ExportTemplate {
+    csrf_token: csrf::CsrfToken,
+    columns: [&'static str; 9],
+    categories: Vec<String>,
+    features: config::Features,
+    // feed token that was just created, only its hash is stored so it is shown once
+    calendar_token: Option<String>,
+}
+
+async fn render_export(
+    state: &AppState,
+    user: &models::User,
+    csrf_token: csrf::CsrfToken,
+    calendar_token: Option<String>,
+) -> Result<Response<Body>, error::AppError> {
+    let context = ExportTemplate {
+        csrf_token,
+        columns: export::CSV_COLUMNS,
+        categories: category_names(&state.db, user).await?,
+        features: state.features,
+        calendar_token,
+    };
+    Ok(Html(context.render()?).into_response())
+}
+
+#[axum_macros::debug_handler]
+pub async fn export_handler(
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+) -> Result<Response<Body>, error::AppError> {
+    render_export(&state, &user, csrf_token, None).await
+}
+
+// replace the calendar feed token, the previous feed links stop working
+#[axum_macros::debug_handler]
+pub async fn calendar_token_handler(
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+) -> Result<Response<Body>, error::AppError> {
+    let token = auth::calendar_token(user.id);
+    state
+        .db
+        .set_calendar_token_hash(user.id, &auth::token_hash(&token))
+        .await?;
+    render_export(&state, &user, csrf_token, Some(token)).await
+}
+
+// CSV EXPORT HANDLER
+#[derive(Deserialize, Debug)]
+pub struct ExportQuery {
+    pub columns: Option<String>,
+    pub category: Option<String>,
+    pub status: Option<String>,
+}
+
+// filter projects by optional category and status, empty category means all
+fn filter_projects(projects: Vec<models::Project>, query: &ExportQuery) -> Vec<models::Project> {
+    projects
+        .into_iter()
+        .filter(|project| {
+            query
+                .category
+                .as_deref()
+                .is_none_or(|category| category.is_empty() || project.category == category)
+        })
+        .filter(|project| {
+            query
+                .status
+                .as_deref()
+                .and_then(|status| status.parse::<u64>().ok())
+                .is_none_or(|status| project.status == status)
+        })
+        .collect()
+}
+
+#[axum_macros::debug_handler]
+pub async fn csv_export_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, error::AppError> {
+    let projects = filter_projects(db.get_projects(user.id).await?, &query);
+    let columns: Vec<&str> = match query.columns.as_deref() {
+        Some(columns) if !columns.is_empty() => columns.split(',').map(str::trim).collect(),
+        _ => export::CSV_COLUMNS.to_vec(),
+    };
+    let csv = export::projects_to_csv(&projects, &columns)?;
+
     let filename = Local::now()
-        .format("backup_%Y-%m-%d_%H-%M-%S.json")
+        .format("projects_%Y-%m-%d_%H-%M-%S.csv")
         .to_string();
-    // return response with json file
     let response = Response::builder()
         .status(StatusCode::OK)
-        .header("Content-Type", "application/json")
+        .header("Content-Type", "text/csv; charset=utf-8")
         .header(
             "Content-Disposition",
             format!("attachment; filename={}", filename),
         )
-        .body(json_str)
-        .unwrap();
+        .body(csv)?;
     Ok(response)
 }
 
-// UPLOAD HANDLER
+// MARKDOWN EXPORT HANDLER
 #[derive(Template, Debug)]
-#[template(path = "restore.html")]
-struct RestoreTemplate {
+#[template(path = "backlog.md")]
+struct BacklogMarkdownTemplate {
+    groups: Vec<export::CategoryExport>,
 }
 
 #[axum_macros::debug_handler]
-pub async fn upload_handler() -> Result<impl IntoResponse, error::AppError> {
-    let context = RestoreTemplate {};
-    let html = context.render()?;
-    Ok(Html(html))
+pub async fn markdown_export_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+) -> Result<impl IntoResponse, error::AppError> {
+    let projects = db.get_projects(user.id).await?;
+    let categories = category_names(&db, &user).await?;
+    let categories: Vec<&str> = categories.iter().map(String::as_str).collect();
+    let context = BacklogMarkdownTemplate {
+        groups: export::group_by_category(projects, &categories),
+    };
+    let markdown = context.render()?;
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/markdown; charset=utf-8")
+        .body(markdown)?;
+    Ok(response)
+}
+
+// TODO.TXT EXPORT HANDLER
+#[derive(Deserialize, Debug)]
+pub struct TodoExportQuery {
+    #[serde(default)]
+    pub category_as: export::TodoTag,
 }
 
-// RESTORE HANDLER
 #[axum_macros::debug_handler]
-pub async fn restore_handler(
-    State(pool): State<MySqlPool>,
-    mut multipart: Multipart,
+pub async fn todo_export_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Query(query): Query<TodoExportQuery>,
 ) -> Result<impl IntoResponse, error::AppError> {
-    // read multipart form data
-    while let Some(mut field) = multipart.next_field().await? {
-        if let Some(file_name) = field.file_name() {
-            if file_name.to_string().ends_with(".json") {
-                let mut bytes = Vec::new();
-                while let Some(chunk) = field.chunk().await? {
-                    bytes.extend_from_slice(&chunk);
-                }
-                // parse json and restore projects
-                let projects: Vec<models::Project> = serde_json::from_slice(&bytes)?;
-                db::restore_projects(&pool, projects).await?;
-            }
-        }
-    }
-    // return response with restore complete message
+    let projects = db.get_projects(user.id).await?;
+    let categories = category_names(&db, &user).await?;
+    let categories: Vec<&str> = categories.iter().map(String::as_str).collect();
+    let groups = export::group_by_category(projects, &categories);
+    let todo = export::projects_to_todo_txt(&groups, query.category_as);
+
     let response = Response::builder()
         .status(StatusCode::OK)
-        .header("Content-Type", "text/html")
-        .body(Body::from(
-            r#"
-                Restore complete!"
-                <a href="/" class="bg-gray-500 text-white px-2 py-1 rounded hover:bg-gray-700 mb-4">
-                    Back
-                </a>
-            "#
-        ))
-        .unwrap();
-
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .header("Content-Disposition", "attachment; filename=todo.txt")
+        .body(todo)?;
     Ok(response)
 }
+
+// CALENDAR HANDLER
+#[derive(Deserialize, Debug)]
+pub struct CalendarQuery {
+    pub category: Option<String>,
+}
+
+#[axum_macros::debug_handler]
+pub async fn calendar_handler(
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    Query(query): Query<CalendarQuery>,
+) -> Result<Response<Body>, error::AppError> {
+    let projects: Vec<models::Project> = state
+        .db
+        .get_projects(user.id)
+        .await?
+        .into_iter()
+        .filter(|project| {
+            query
+                .category
+                .as_deref()
+                .is_none_or(|category| category.is_empty() || project.category == category)
+        })
+        .collect();
+    let name = match query.category.as_deref() {
+        Some(category) if !category.is_empty() => format!("Projects - {}", category),
+        _ => "Projects".to_string(),
+    };
+    let calendar = ical::calendar(&projects, &name, Local::now().date_naive());
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/calendar; charset=utf-8")
+        .header("Content-Disposition", "inline; filename=projects.ics")
+        .body(Body::from(calendar))?;
+    Ok(response)
+}
+
+// FEED HANDLERS
+#[derive(Template, Debug)]
+#[template(path = "feed.xml")]
+struct FeedTemplate {
+    feed: feed::Feed,
+}
+
+fn feed_response(feed: feed::Feed) -> Result<Response<Body>, error::AppError> {
+    let xml = FeedTemplate { feed }.render()?;
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/atom+xml; charset=utf-8")
+        .body(Body::from(xml))?;
+    Ok(response)
+}
+
+#[axum_macros::debug_handler]
+pub async fn completed_feed_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+) -> Result<Response<Body>, error::AppError> {
+    let page = db
+        .get_completed_projects(user.id, 1, feed::FEED_LENGTH)
+        .await?;
+    feed_response(feed::completed_feed(&page.projects))
+}
+
+#[axum_macros::debug_handler]
+pub async fn activity_feed_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+) -> Result<Response<Body>, error::AppError> {
+    let activity = db.get_recent_activity(user.id, feed::FEED_LENGTH).await?;
+    feed_response(feed::activity_feed(&activity))
+}
+
+// IMPORT HANDLER
+#[derive(Template, Debug)]
+#[template(path = "import.html")]
+struct ImportTemplate {
+    csrf_token: csrf::CsrfToken,
+    message: Option<String>,
+}
+
+#[axum_macros::debug_handler]
+pub async fn import_handler(
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+) -> Result<impl IntoResponse, error::AppError> {
+    let context = ImportTemplate {
+        csrf_token,
+        message: None,
+    };
+    let html = context.render()?;
+    Ok(Html(html))
+}
+
+// CSV IMPORT HANDLER
+#[derive(Template, Debug)]
+#[template(path = "import_csv.html")]
+struct CsvMappingTemplate {
+    csrf_token: csrf::CsrfToken,
+    data: String,
+    headers: Vec<String>,
+    preview: Vec<Vec<String>>,
+    // selected column per project field
+    fields: Vec<(&'static str, Option<usize>)>,
+    default_category: String,
+    errors: Vec<import::RowError>,
+    categories: Vec<String>,
+}
+
+impl CsvMappingTemplate {
+    fn new(
+        data: String,
+        table: import::CsvTable,
+        fields: Vec<(&'static str, Option<usize>)>,
+        categories: Vec<String>,
+        csrf_token: csrf::CsrfToken,
+    ) -> Self {
+        CsvMappingTemplate {
+            csrf_token,
+            data,
+            headers: table.headers,
+            preview: table.rows.into_iter().take(5).collect(),
+            fields,
+            default_category: categories.first().cloned().unwrap_or_default(),
+            errors: Vec::new(),
+            categories,
+        }
+    }
+}
+
+// uploads cut off by the body limit or otherwise broken
+fn upload_error(err: MultipartError) -> error::AppError {
+    if err.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        let limit = validate::MAX_UPLOAD_SIZE / 1024 / 1024;
+        error::AppError::Validation(format!("The file is larger than {} MB", limit))
+    } else {
+        error::AppError::Validation(err.body_text())
+    }
+}
+
+// uploaded export files that cannot be read are the user's to fix
+fn invalid_upload(err: impl std::fmt::Display) -> error::AppError {
+    error::AppError::Validation(format!("The file could not be read: {}", err))
+}
+
+// read the first uploaded file of a multipart form as text
+async fn read_upload(
+    multipart: &mut Multipart,
+) -> Result<Option<(String, String)>, error::AppError> {
+    while let Some(mut field) = multipart.next_field().await.map_err(upload_error)? {
+        if let Some(file_name) = field.file_name() {
+            let file_name = file_name.to_string();
+            let mut bytes = Vec::new();
+            while let Some(chunk) = field.chunk().await.map_err(upload_error)? {
+                bytes.extend_from_slice(&chunk);
+            }
+            return Ok(Some((
+                file_name,
+                String::from_utf8(bytes).map_err(invalid_upload)?,
+            )));
+        }
+    }
+    Ok(None)
+}
+
+#[axum_macros::debug_handler]
+pub async fn csv_import_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, error::AppError> {
+    let Some((_, data)) = read_upload(&mut multipart).await? else {
+        let context = ImportTemplate {
+            csrf_token,
+            message: Some("No file uploaded".to_string()),
+        };
+        return Ok(Html(context.render()?));
+    };
+
+    // preselect columns whose header matches a field name
+    let table = import::parse_csv(&data).map_err(invalid_upload)?;
+    let fields = import::CSV_FIELDS
+        .iter()
+        .map(|field| (*field, import::guess_column(&table.headers, field)))
+        .collect();
+    let context = CsvMappingTemplate::new(
+        data,
+        table,
+        fields,
+        category_names(&db, &user).await?,
+        csrf_token,
+    );
+    let html = context.render()?;
+    Ok(Html(html))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CsvMappingQuery {
+    pub data: String,
+    pub default_category: String,
+    pub name: String,
+    pub category: String,
+    pub status: String,
+    pub notes: String,
+    pub creation_date: String,
+    pub start_date: String,
+    pub completion_date: String,
+}
+
+#[axum_macros::debug_handler]
+pub async fn csv_import_confirm_handler(
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+    Form(query): Form<CsvMappingQuery>,
+) -> Result<impl IntoResponse, error::AppError> {
+    let column = |value: &str| value.parse::<usize>().ok();
+    let fields = vec![
+        ("name", column(&query.name)),
+        ("category", column(&query.category)),
+        ("status", column(&query.status)),
+        ("notes", column(&query.notes)),
+        ("creation_date", column(&query.creation_date)),
+        ("start_date", column(&query.start_date)),
+        ("completion_date", column(&query.completion_date)),
+    ];
+
+    let table = import::parse_csv(&query.data).map_err(invalid_upload)?;
+    let categories = category_names(&state.db, &user).await?;
+    let (projects, errors) = import::csv_projects(
+        &table,
+        &fields,
+        &query.default_category,
+        &categories.iter().map(String::as_str).collect::<Vec<_>>(),
+        Local::now().date_naive(),
+    );
+
+    // show mapping again with errors, nothing is imported until every row is valid
+    if !errors.is_empty() {
+        let mut context =
+            CsvMappingTemplate::new(query.data, table, fields, categories, csrf_token);
+        context.default_category = query.default_category;
+        context.errors = errors;
+        let html = context.render()?;
+        return Ok(Html(html));
+    }
+
+    state.db.import_projects(user.id, &projects).await?;
+    for category in &categories {
+        state.notify(user.id, category).await;
+    }
+    let context = ImportTemplate {
+        csrf_token,
+        message: Some(format!("Imported {} projects", projects.len())),
+    };
+    let html = context.render()?;
+    Ok(Html(html))
+}
+
+// TOOL IMPORT HANDLER
+#[derive(Template, Debug)]
+#[template(path = "import_preview.html")]
+struct ImportPreviewTemplate {
+    csrf_token: csrf::CsrfToken,
+    source: &'static str,
+    data: String,
+    // list name, number of items and preselected category
+    lists: Vec<(String, usize, String)>,
+    preview: Vec<import::ImportedItem>,
+    total: usize,
+    categories: Vec<String>,
+    // items that are not imported
+    errors: Vec<import::RowError>,
+}
+
+#[axum_macros::debug_handler]
+pub async fn tool_import_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, error::AppError> {
+    // read source selection and export file
+    let mut source = None;
+    let mut data = None;
+    while let Some(mut field) = multipart.next_field().await.map_err(upload_error)? {
+        if field.name() == Some("source") {
+            source = import::Source::parse(&field.text().await.map_err(upload_error)?);
+        } else if field.file_name().is_some() {
+            let mut bytes = Vec::new();
+            while let Some(chunk) = field.chunk().await.map_err(upload_error)? {
+                bytes.extend_from_slice(&chunk);
+            }
+            data = Some(String::from_utf8(bytes).map_err(invalid_upload)?);
+        }
+    }
+    let (Some(source), Some(data)) = (source, data) else {
+        let context = ImportTemplate {
+            csrf_token,
+            message: Some("Select a source and an export file".to_string()),
+        };
+        return Ok(Html(context.render()?));
+    };
+
+    let (items, errors) =
+        import::parse_source(source, &data, Local::now().date_naive()).map_err(invalid_upload)?;
+    let categories = category_names(&db, &user).await?;
+    let lists = import::lists(&items)
+        .into_iter()
+        .map(|(name, count)| {
+            // preselect the category with the same name as the list
+            let category = categories
+                .iter()
+                .find(|category| category.eq_ignore_ascii_case(&name))
+                .or(categories.first())
+                .cloned()
+                .unwrap_or_default();
+            (name, count, category)
+        })
+        .collect();
+    let total = items.len();
+
+    let context = ImportPreviewTemplate {
+        csrf_token,
+        source: source.as_str(),
+        data,
+        lists,
+        preview: items.into_iter().take(20).collect(),
+        total,
+        categories,
+        errors,
+    };
+    let html = context.render()?;
+    Ok(Html(html))
+}
+
+#[axum_macros::debug_handler]
+pub async fn tool_import_confirm_handler(
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Result<impl IntoResponse, error::AppError> {
+    let field = |name: &str| {
+        form.iter()
+            .find(|(key, _)| key == name)
+            .map_or("", |(_, value)| value.as_str())
+    };
+    let Some(source) = import::Source::parse(field("source")) else {
+        let context = ImportTemplate {
+            csrf_token,
+            message: Some("Unknown import source".to_string()),
+        };
+        return Ok(Html(context.render()?));
+    };
+
+    let (items, errors) = import::parse_source(source, field("data"), Local::now().date_naive())
+        .map_err(invalid_upload)?;
+    let lists = import::lists(&items);
+    let categories = category_names(&state.db, &user).await?;
+
+    // lists are mapped by index, an empty category skips the list
+    let mut projects = Vec::new();
+    for item in items {
+        // never put an item into the category chosen for another list
+        let Some(index) = lists.iter().position(|(name, _)| *name == item.list) else {
+            continue;
+        };
+        let category = field(&format!("list_{}", index));
+        if !categories.iter().any(|name| name == category) {
+            continue;
+        }
+        projects.push(models::NewProject {
+            category: category.to_string(),
+            ..item.project
+        });
+    }
+    state.db.import_projects(user.id, &projects).await?;
+    for category in &categories {
+        state.notify(user.id, category).await;
+    }
+
+    let mut message = format!("Imported {} projects", projects.len());
+    if !errors.is_empty() {
+        message.push_str(&format!(", skipped {} invalid items", errors.len()));
+    }
+    let context = ImportTemplate {
+        csrf_token,
+        message: Some(message),
+    };
+    let html = context.render()?;
+    Ok(Html(html))
+}
+
+// WEBHOOKS HANDLER
+#[derive(Template, Debug)]
+#[template(path = "webhooks.html")]
+struct WebhooksTemplate {
+    csrf_token: csrf::CsrfToken,
+    webhooks: Vec<models::Webhook>,
+    events: [&'static str; 4],
+    error: Option<String>,
+}
+
+async fn render_webhooks(
+    db: &db::Db,
+    user: &models::User,
+    csrf_token: csrf::CsrfToken,
+    error: Option<String>,
+) -> Result<Response<Body>, error::AppError> {
+    let context = WebhooksTemplate {
+        csrf_token,
+        webhooks: db.get_webhooks(user.id).await?,
+        events: webhooks::EVENTS,
+        error,
+    };
+    Ok(Html(context.render()?).into_response())
+}
+
+#[axum_macros::debug_handler]
+pub async fn webhooks_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+) -> Result<Response<Body>, error::AppError> {
+    render_webhooks(&db, &user, csrf_token, None).await
+}
+
+#[axum_macros::debug_handler]
+pub async fn add_webhook_handler(
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>, error::AppError> {
+    let field = |name: &str| {
+        form.iter()
+            .find(|(key, _)| key == name)
+            .map_or("", |(_, value)| value.trim())
+    };
+    // selected event checkboxes, none selected subscribes to all events
+    let events: Vec<&str> = form
+        .iter()
+        .filter(|(key, value)| key == "events" && webhooks::EVENTS.contains(&value.as_str()))
+        .map(|(_, value)| value.as_str())
+        .collect();
+    let url = match validate::webhook_url(field("url"), &state.webhook_config) {
+        Ok(url) => url,
+        Err(error) => return render_webhooks(&state.db, &user, csrf_token, Some(error)).await,
+    };
+    // generate a signing secret if none is given
+    let secret = match field("secret") {
+        "" => hex::encode(rand::random::<[u8; 32]>()),
+        secret => secret.to_string(),
+    };
+
+    state
+        .db
+        .add_webhook(user.id, url.as_str(), &secret, &events.join(","))
+        .await?;
+    Ok(Redirect::to(&urls::url("/webhooks")).into_response())
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteWebhookQuery {
+    pub id: u64,
+}
+
+#[axum_macros::debug_handler]
+pub async fn delete_webhook_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Form(query): Form<DeleteWebhookQuery>,
+) -> Result<impl IntoResponse, error::AppError> {
+    db.delete_webhook(user.id, query.id).await?;
+    Ok(Redirect::to(&urls::url("/webhooks")))
+}
+
+// WEBHOOK DELIVERIES HANDLER
+#[derive(Template, Debug)]
+#[template(path = "deliveries.html")]
+struct DeliveriesTemplate {
+    deliveries: Vec<models::WebhookDelivery>,
+}
+
+#[axum_macros::debug_handler]
+pub async fn deliveries_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+) -> Result<impl IntoResponse, error::AppError> {
+    let context = DeliveriesTemplate {
+        deliveries: db.get_webhook_deliveries(user.id, 100).await?,
+    };
+    let html = context.render()?;
+    Ok(Html(html))
+}
+
+// CATEGORIES HANDLER
+// share of a category, with the name of the user it is shared with
+#[derive(Debug)]
+pub struct ShareRow {
+    pub id: u64,
+    pub username: String,
+    pub access: String,
+}
+
+#[derive(Template, Debug)]
+#[template(path = "categories.html")]
+struct CategoriesTemplate {
+    csrf_token: csrf::CsrfToken,
+    categories: Vec<(models::Category, Vec<ShareRow>)>,
+    // users categories can be shared with
+    users: Vec<models::UserName>,
+    error: Option<String>,
+}
+
+async fn render_categories(
+    db: &db::Db,
+    user: &models::User,
+    csrf_token: csrf::CsrfToken,
+    error: Option<String>,
+) -> Result<Response<Body>, error::AppError> {
+    let users = db.get_users().await?;
+    let shares = db.get_category_shares(user.id).await?;
+    let categories = db
+        .get_categories(user.id)
+        .await?
+        .into_iter()
+        .map(|category| {
+            let rows = shares
+                .iter()
+                .filter(|share| share.category_id == category.id)
+                .map(|share| ShareRow {
+                    id: share.id,
+                    username: users
+                        .iter()
+                        .find(|other| other.id == share.user_id)
+                        .map_or_else(String::new, |other| other.username.clone()),
+                    access: share.access.clone(),
+                })
+                .collect();
+            (category, rows)
+        })
+        .collect();
+
+    let context = CategoriesTemplate {
+        csrf_token,
+        categories,
+        users: users
+            .into_iter()
+            .filter(|other| other.id != user.id)
+            .collect(),
+        error,
+    };
+    Ok(Html(context.render()?).into_response())
+}
+
+#[axum_macros::debug_handler]
+pub async fn categories_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+) -> Result<Response<Body>, error::AppError> {
+    render_categories(&db, &user, csrf_token, None).await
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AddCategoryQuery {
+    pub name: String,
+}
+
+#[axum_macros::debug_handler]
+pub async fn add_category_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+    Form(query): Form<AddCategoryQuery>,
+) -> Result<Response<Body>, error::AppError> {
+    let name = match validate::category_name(&query.name) {
+        Ok(name) => name,
+        Err(message) => {
+            return render_categories(&db, &user, csrf_token, Some(sentence(message))).await
+        }
+    };
+    db.add_category(user.id, name).await?;
+    Ok(Redirect::to(&urls::url("/categories")).into_response())
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteCategoryQuery {
+    pub id: u64,
+}
+
+#[axum_macros::debug_handler]
+pub async fn delete_category_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+    Form(query): Form<DeleteCategoryQuery>,
+) -> Result<Response<Body>, error::AppError> {
+    if !db.delete_category(user.id, query.id).await? {
+        let error = "Only categories without projects can be deleted".to_string();
+        return render_categories(&db, &user, csrf_token, Some(error)).await;
+    }
+    Ok(Redirect::to(&urls::url("/categories")).into_response())
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ShareQuery {
+    pub category_id: u64,
+    pub user_id: u64,
+    pub access: String,
+}
+
+#[axum_macros::debug_handler]
+pub async fn share_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+    Form(query): Form<ShareQuery>,
+) -> Result<Response<Body>, error::AppError> {
+    // only the owner can share a category, and not with themselves
+    let category = db.get_category(query.category_id).await?;
+    let Some(category) = category.filter(|category| category.owner_id == user.id) else {
+        return Err(error::AppError::NotFound);
+    };
+    let Some(access) = models::Access::parse(&query.access) else {
+        return render_categories(&db, &user, csrf_token, Some("Unknown access".to_string())).await;
+    };
+    if query.user_id == user.id {
+        let error = "Categories cannot be shared with yourself".to_string();
+        return render_categories(&db, &user, csrf_token, Some(error)).await;
+    }
+
+    db.share_category(category.id, query.user_id, access)
+        .await?;
+    Ok(Redirect::to(&urls::url("/categories")).into_response())
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UnshareQuery {
+    pub id: u64,
+}
+
+#[axum_macros::debug_handler]
+pub async fn unshare_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Form(query): Form<UnshareQuery>,
+) -> Result<Response<Body>, error::AppError> {
+    db.delete_share(user.id, query.id).await?;
+    Ok(Redirect::to(&urls::url("/categories")).into_response())
+}
+
+// API TOKENS HANDLER
+#[derive(Template, Debug)]
+#[template(path = "tokens.html")]
+struct TokensTemplate {
+    csrf_token: csrf::CsrfToken,
+    tokens: Vec<models::ApiToken>,
+    scopes: [models::Scope; 3],
+    // newly created token, only ever shown once
+    created: Option<String>,
+    error: Option<String>,
+}
+
+async fn render_tokens(
+    db: &db::Db,
+    user: &models::User,
+    csrf_token: csrf::CsrfToken,
+    created: Option<String>,
+    error: Option<String>,
+) -> Result<Response<Body>, error::AppError> {
+    let context = TokensTemplate {
+        csrf_token,
+        tokens: db.get_api_tokens(user.id).await?,
+        scopes: models::Scope::ALL,
+        created,
+        error,
+    };
+    Ok(Html(context.render()?).into_response())
+}
+
+#[axum_macros::debug_handler]
+pub async fn tokens_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+) -> Result<Response<Body>, error::AppError> {
+    render_tokens(&db, &user, csrf_token, None, None).await
+}
+
+#[axum_macros::debug_handler]
+pub async fn add_token_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>, error::AppError> {
+    let name = form
+        .iter()
+        .find(|(key, _)| key == "name")
+        .map_or("", |(_, value)| value.trim());
+    // selected scope checkboxes
+    let scopes: Vec<&str> = form
+        .iter()
+        .filter(|(key, _)| key == "scopes")
+        .filter_map(|(_, value)| models::Scope::parse(value))
+        .map(|scope| scope.as_str())
+        .collect();
+    if name.is_empty() || scopes.is_empty() {
+        let error = "A token needs a name and at least one scope".to_string();
+        return render_tokens(&db, &user, csrf_token, None, Some(error)).await;
+    }
+
+    let token = format!("pt_{}", hex::encode(rand::random::<[u8; 32]>()));
+    db.add_api_token(user.id, name, &auth::token_hash(&token), &scopes.join(","))
+        .await?;
+    render_tokens(&db, &user, csrf_token, Some(token), None).await
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteTokenQuery {
+    pub id: u64,
+}
+
+#[axum_macros::debug_handler]
+pub async fn delete_token_handler(
+    State(db): State<db::Db>,
+    Extension(user): Extension<models::User>,
+    Form(query): Form<DeleteTokenQuery>,
+) -> Result<impl IntoResponse, error::AppError> {
+    db.delete_api_token(user.id, query.id).await?;
+    Ok(Redirect::to(&urls::url("/tokens")))
+}
+
+// UPLOAD HANDLER
+#[derive(Template, Debug)]
+#[template(path = "restore.html")]
+struct RestoreTemplate {
+    csrf_token: csrf::CsrfToken,
+}
+
+#[axum_macros::debug_handler]
+pub async fn upload_handler(
+    Extension(csrf_token): Extension<csrf::CsrfToken>,
+) -> Result<impl IntoResponse, error::AppError> {
+    let context = RestoreTemplate { csrf_token };
+    let html = context.render()?;
+    Ok(Html(html))
+}
+
+// RESTORE HANDLER
+// a backup is held to the same limits as projects entered by hand, categories are not created by a restore
+fn backup_projects(
+    projects: Vec<models::Project>,
+    categories: &[String],
+) -> Result<Vec<models::Project>, error::AppError> {
+    projects
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut project)| {
+            let result = (|| {
+                project.name = validate::project_name(&project.name)?.to_string();
+                validate::notes(&project.notes)?;
+                let category = validate::category_name(&project.category)?;
+                if !categories.iter().any(|name| name == category) {
+                    return Err(format!("unknown category '{}', create it first", category));
+                }
+                project.category = category.to_string();
+                if project.status > 2 {
+                    return Err(format!("unknown status {}", project.status));
+                }
+                Ok(project)
+            })();
+            result.map_err(|message| {
+                error::AppError::BadBackup(format!("project {}: {}", index + 1, message))
+            })
+        })
+        .collect()
+}
+
+#[axum_macros::debug_handler]
+pub async fn restore_handler(
+    State(state): State<AppState>,
+    Extension(user): Extension<models::User>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, error::AppError> {
+    // read multipart form data
+    let mut restored = false;
+    while let Some(mut field) = multipart.next_field().await.map_err(upload_error)? {
+        if let Some(file_name) = field.file_name() {
+            if !file_name.ends_with(".json") {
+                return Err(error::AppError::BadBackup(
+                    "backups are .json files".to_string(),
+                ));
+            }
+            let mut bytes = Vec::new();
+            while let Some(chunk) = field.chunk().await.map_err(upload_error)? {
+                bytes.extend_from_slice(&chunk);
+            }
+            // parse json and restore projects
+            let projects: Vec<models::Project> = serde_json::from_slice(&bytes)
+                .map_err(|err| error::AppError::BadBackup(err.to_string()))?;
+            let projects = backup_projects(projects, &category_names(&state.db, &user).await?)?;
+            // keep the replaced projects, a restore of the wrong file can then be undone
+            if let Some(backup_dir) = &state.backup_dir {
+                let filename = Local::now()
+                    .format(&format!("{}-backup_%Y-%m-%d_%H-%M-%S.json", user.id))
+                    .to_string();
+                tokio::fs::write(
+                    backup_dir.join(&filename),
+                    backup_json(&state.db, &user).await?,
+                )
+                .await?;
+                println!(
+                    "{} - Restore - saved previous projects to {}",
+                    Local::now(),
+                    filename
+                );
+            }
+            state.db.restore_projects(user.id, projects).await?;
+            for category in category_names(&state.db, &user).await? {
+                state.notify(user.id, &category).await;
+            }
+            restored = true;
+        }
+    }
+    if !restored {
+        return Err(error::AppError::BadBackup(
+            "no backup file was uploaded".to_string(),
+        ));
+    }
+    // return response with restore complete message
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/html")
+        .body(Body::from(format!(
+            r#"
+                Restore complete!"
+                <a href="{}" class="bg-gray-500 text-white px-2 py-1 rounded hover:bg-gray-700 mb-4">
+                    Back
+                </a>
+            "#,
+            urls::url("/")
+        )))
+        .unwrap();
+
+    Ok(response)
+}
+
+// LOGIN HANDLER
+#[derive(Template, Debug)]
+#[template(path = "login.html")]
+struct LoginTemplate {
+    // creating the first account instead of signing in
+    setup: bool,
+    username: String,
+    error: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LoginQuery {
+    pub username: String,
+    pub password: String,
+}
+
+#[axum_macros::debug_handler]
+pub async fn login_handler(State(db): State<db::Db>) -> Result<impl IntoResponse, error::AppError> {
+    // first run, there is nobody to sign in as yet
+    if db.count_users().await? == 0 {
+        return Ok(Redirect::to(&urls::url("/setup")).into_response());
+    }
+    let context = LoginTemplate {
+        setup: false,
+        username: String::new(),
+        error: None,
+    };
+    Ok(Html(context.render()?).into_response())
+}
+
+#[axum_macros::debug_handler]
+pub async fn login_submit_handler(
+    State(state): State<AppState>,
+    Form(query): Form<LoginQuery>,
+) -> Result<impl IntoResponse, error::AppError> {
+    let Some(user) = auth::login(&state.db, query.username.trim(), &query.password).await? else {
+        let context = LoginTemplate {
+            setup: false,
+            username: query.username,
+            error: Some("Wrong username or password".to_string()),
+        };
+        return Ok((StatusCode::UNAUTHORIZED, Html(context.render()?)).into_response());
+    };
+
+    let cookie = auth::start_session(&state.db, user.id, state.secure_cookies).await?;
+    println!("{} - Auth - {} signed in", Local::now(), user.username);
+    Ok((
+        [(header::SET_COOKIE, cookie)],
+        Redirect::to(&urls::url("/")),
+    )
+        .into_response())
+}
+
+#[axum_macros::debug_handler]
+pub async fn logout_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, error::AppError> {
+    if let Some(token) = auth::cookie(&headers, auth::SESSION_COOKIE) {
+        state.db.delete_session(&auth::token_hash(token)).await?;
+    }
+    let cookie = auth::session_cookie("", 0, state.secure_cookies);
+    Ok((
+        [(header::SET_COOKIE, cookie)],
+        Redirect::to(&urls::url("/login")),
+    ))
+}
+
+// SETUP HANDLER
+#[axum_macros::debug_handler]
+pub async fn setup_handler(State(db): State<db::Db>) -> Result<impl IntoResponse, error::AppError> {
+    if db.count_users().await? > 0 {
+        return Ok(Redirect::to(&urls::url("/login")).into_response());
+    }
+    let context = LoginTemplate {
+        setup: true,
+        username: String::new(),
+        error: None,
+    };
+    Ok(Html(context.render()?).into_response())
+}
+
+#[axum_macros::debug_handler]
+pub async fn setup_submit_handler(
+    State(state): State<AppState>,
+    Form(query): Form<LoginQuery>,
+) -> Result<impl IntoResponse, error::AppError> {
+    // the first run flow can only create the very first account
+    if state.db.count_users().await? > 0 {
+        return Ok(Redirect::to(&urls::url("/login")).into_response());
+    }
+
+    let username = query.username.trim();
+    let error = if username.is_empty() {
+        Some("Username is empty".to_string())
+    } else if query.password.chars().count() < auth::MIN_PASSWORD_LENGTH {
+        Some(format!(
+            "Password needs at least {} characters",
+            auth::MIN_PASSWORD_LENGTH
+        ))
+    } else {
+        None
+    };
+    if error.is_some() {
+        let context = LoginTemplate {
+            setup: true,
+            username: username.to_string(),
+            error,
+        };
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(context.render()?)).into_response());
+    }
+
+    // checked again when the user is added, another setup may have finished in the meantime
+    let password_hash = auth::hash_password(&query.password)?;
+    let Some(user_id) = state
+        .db
+        .add_first_user(username, &password_hash, &state.default_categories)
+        .await?
+    else {
+        return Ok(Redirect::to(&urls::url("/login")).into_response());
+    };
+    let cookie = auth::start_session(&state.db, user_id, state.secure_cookies).await?;
+    Ok((
+        [(header::SET_COOKIE, cookie)],
+        Redirect::to(&urls::url("/")),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state;
+
+    #[test]
+    fn repeated_query_values_keep_commas() {
+        let query =
+            "start=2026-01-01&category=Home%2C+Garden&category=Work&category=&granularity=week";
+        assert_eq!(
+            query_values(Some(query), "category"),
+            ["Home, Garden", "Work"]
+        );
+        assert!(query_values(Some("start=2026-01-01"), "category").is_empty());
+        assert!(query_values(None, "category").is_empty());
+    }
+
+    #[tokio::test]
+    async fn projects_of_other_users_are_only_reached_through_shares() {
+        let state = state::test_state().await;
+        let db = &state.db;
+        let categories = ["Work".to_string(), "Home".to_string()];
+        let alice = db.add_user("alice", "hash", &categories).await.unwrap();
+        let bob = db.add_user("bob", "hash", &[]).await.unwrap();
+        let bob = db.get_user(bob).await.unwrap().unwrap();
+        let mut ids = Vec::new();
+        for (name, category) in [("Report", "Work"), ("Slides", "Work"), ("Beds", "Home")] {
+            let id = db
+                .add_project(alice, name.to_string(), category.to_string())
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        let work = db
+            .get_category_by_name(alice, "Work")
+            .await
+            .unwrap()
+            .unwrap();
+        let position = |id| async move { db.get_project(id).await.unwrap().map(|p| p.position) };
+        let list = || list_handler(State(db.clone()), Extension(bob.clone()), Path(work.id));
+        let up = |id| {
+            up_handler(
+                State(state.clone()),
+                Extension(bob.clone()),
+                Form(MoveQuery { id, version: 1 }),
+            )
+        };
+        let delete = |id| {
+            delete_handler(
+                State(state.clone()),
+                Extension(bob.clone()),
+                Extension(csrf::CsrfToken::default()),
+                Form(DeleteQuery { id, version: 1 }),
+            )
+        };
+
+        // unshared projects are not found, nothing is changed
+        assert!(load_project(db, &bob, ids[1]).await.unwrap().is_none());
+        assert!(matches!(list().await, Err(error::AppError::NotFound)));
+        assert!(matches!(up(ids[1]).await, Err(error::AppError::NotFound)));
+        assert!(matches!(
+            delete(ids[1]).await,
+            Err(error::AppError::NotFound)
+        ));
+        assert_eq!(position(ids[1]).await, Some(2));
+
+        // a read share shows the category, the other categories stay hidden
+        db.share_category(work.id, bob.id, models::Access::Read)
+            .await
+            .unwrap();
+        assert!(matches!(
+            load_project(db, &bob, ids[1]).await.unwrap(),
+            Some((_, false))
+        ));
+        assert!(load_project(db, &bob, ids[2]).await.unwrap().is_none());
+        assert!(list().await.is_ok());
+        assert!(matches!(up(ids[1]).await, Err(error::AppError::NotFound)));
+        assert!(matches!(
+            delete(ids[1]).await,
+            Err(error::AppError::NotFound)
+        ));
+        assert_eq!(position(ids[1]).await, Some(2));
+
+        // an edit share lets bob reorder and delete
+        db.share_category(work.id, bob.id, models::Access::Edit)
+            .await
+            .unwrap();
+        assert!(up(ids[1]).await.is_ok());
+        assert_eq!(position(ids[1]).await, Some(1));
+        assert_eq!(position(ids[0]).await, Some(2));
+        assert!(delete(ids[1]).await.is_ok());
+        assert_eq!(position(ids[1]).await, None);
+        assert_eq!(position(ids[0]).await, Some(1));
+        assert!(matches!(
+            delete(ids[2]).await,
+            Err(error::AppError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn completed_feed_is_escaped_and_newest_first() {
+        let db = db::test_db().await;
+        let user_id = db.add_user("alice", "hash", &[]).await.unwrap();
+        let user = db.get_user(user_id).await.unwrap().unwrap();
+        let date = |day| NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+        let project = |name: &str, category: &str, notes: &str, day| models::NewProject {
+            name: name.to_string(),
+            category: category.to_string(),
+            status: 2,
+            notes: notes.to_string(),
+            creation_date: date(1),
+            start_date: date(1),
+            completion_date: date(day),
+        };
+        db.import_projects(
+            user_id,
+            &[
+                project("Older", "Work", "", 3),
+                project("Tom & Jerry <draft>", "R&D", "a < b", 9),
+                project("Middle", "Work", "", 5),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let response = completed_feed_handler(State(db), Extension(user))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let xml = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(xml.contains("<title>Completed: Tom &amp; Jerry &lt;draft&gt;</title>"));
+        assert!(xml.contains("term=\"R&amp;D\""));
+        assert!(xml.contains(">a &lt; b</content>"));
+        assert!(!xml.contains("<draft>"));
+        let position = |title: &str| xml.find(title).unwrap();
+        assert!(position("Tom &amp; Jerry") < position("Completed: Middle"));
+        assert!(position("Completed: Middle") < position("Completed: Older"));
+        // the feed is as new as its newest entry
+        let updated = "<updated>2026-03-09T00:00:00+00:00</updated>";
+        assert!(xml.find(updated).unwrap() < xml.find("<entry>").unwrap());
+    }
+}
@@ -0,0 +1,218 @@
+use chrono::{Days, NaiveDate, Utc};
+
+use crate::models;
+
+// escape TEXT values (RFC 5545 section 3.3.11)
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// fold content lines longer than 75 octets (RFC 5545 section 3.1)
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for ch in line.chars() {
+        if length + ch.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(ch);
+        length += ch.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+// started and completed projects become all day events, backlog projects become todos
+pub fn calendar(projects: &[models::Project], name: &str, today: NaiveDate) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//project-tracker//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+
+    for project in projects {
+        let component = if project.status == 0 {
+            "VTODO"
+        } else {
+            "VEVENT"
+        };
+        lines.push(format!("BEGIN:{}", component));
+        lines.push(format!("UID:project-{}@project-tracker", project.id));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("SUMMARY:{}", escape(&project.name)));
+        lines.push(format!("CATEGORIES:{}", escape(&project.category)));
+        lines.push(format!("CREATED:{}T000000", date(project.creation_date)));
+        if !project.notes.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape(&project.notes)));
+        }
+        match project.status {
+            0 => lines.push("STATUS:NEEDS-ACTION".to_string()),
+            // end dates of all day events are exclusive, in progress projects run until today
+            1 => {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", date(project.start_date)));
+                lines.push(format!(
+                    "DTEND;VALUE=DATE:{}",
+                    date(today.max(project.start_date) + Days::new(1))
+                ));
+                lines.push("STATUS:TENTATIVE".to_string());
+            }
+            _ => {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", date(project.start_date)));
+                lines.push(format!(
+                    "DTEND;VALUE=DATE:{}",
+                    date(project.completion_date.max(project.start_date) + Days::new(1))
+                ));
+                lines.push("STATUS:CONFIRMED".to_string());
+            }
+        }
+        lines.push(format!("END:{}", component));
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::Project;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    // content lines of a calendar with folded lines joined again
+    fn unfolded(calendar: &str) -> Vec<String> {
+        calendar
+            .replace("\r\n ", "")
+            .split_terminator("\r\n")
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(escape("a\\b;c,d\r\ne\nf"), "a\\\\b\\;c\\,d\\ne\\nf");
+        assert_eq!(escape("plain: text"), "plain: text");
+    }
+
+    #[test]
+    fn short_lines_are_not_folded() {
+        let line = "x".repeat(75);
+        assert_eq!(fold(&line), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn long_lines_fold_at_75_octets() {
+        let line = "x".repeat(200);
+        let folded = fold(&line);
+        let parts: Vec<_> = folded.split_terminator("\r\n").collect();
+        assert_eq!(
+            parts.iter().map(|part| part.len()).collect::<Vec<_>>(),
+            [75, 75, 52]
+        );
+        assert!(parts[1..].iter().all(|part| part.starts_with(' ')));
+        assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn folding_keeps_multibyte_characters_whole() {
+        // 74 octets, a two octet character does not fit in the first line
+        let line = format!("{}ééé", "x".repeat(74));
+        let folded = fold(&line);
+        let parts: Vec<_> = folded.split_terminator("\r\n").collect();
+        assert_eq!(parts, [&"x".repeat(74), " ééé"]);
+        for part in parts {
+            assert!(part.len() <= 75);
+        }
+
+        let line = "€".repeat(60);
+        let folded = fold(&line);
+        assert!(folded.split_terminator("\r\n").all(|part| part.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn calendar_lines_end_with_crlf() {
+        let calendar = calendar(&[], "Work, home", day(1));
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(!calendar.replace("\r\n", "").contains('\n'));
+        assert!(unfolded(&calendar).contains(&"X-WR-CALNAME:Work\\, home".to_string()));
+    }
+
+    #[test]
+    fn backlog_projects_become_todos() {
+        let project = Project {
+            notes: "line one\nline two".to_string(),
+            ..Project::example(3, "Plan; then build", "Home", day(2))
+        };
+        let lines = unfolded(&calendar(&[project], "Projects", day(10)));
+        let start = lines.iter().position(|line| line == "BEGIN:VTODO").unwrap();
+        let end = lines.iter().position(|line| line == "END:VTODO").unwrap();
+        let todo = &lines[start..=end];
+        assert!(todo.contains(&"UID:project-3@project-tracker".to_string()));
+        assert!(todo.contains(&"SUMMARY:Plan\\; then build".to_string()));
+        assert!(todo.contains(&"DESCRIPTION:line one\\nline two".to_string()));
+        assert!(todo.contains(&"STATUS:NEEDS-ACTION".to_string()));
+        assert!(!todo.iter().any(|line| line.starts_with("DTSTART")));
+    }
+
+    #[test]
+    fn started_projects_run_until_today() {
+        let project = Project {
+            status: 1,
+            ..Project::example(1, "A", "Work", day(2))
+        };
+        let lines = unfolded(&calendar(
+            std::slice::from_ref(&project),
+            "Projects",
+            day(10),
+        ));
+        assert!(lines.contains(&"BEGIN:VEVENT".to_string()));
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20260302".to_string()));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20260311".to_string()));
+        assert!(!lines.iter().any(|line| line.starts_with("DESCRIPTION")));
+
+        // a start date in the future still gives a one day event
+        let lines = unfolded(&calendar(&[project], "Projects", day(1)));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20260303".to_string()));
+    }
+
+    #[test]
+    fn completed_projects_end_after_completion() {
+        let project = Project {
+            status: 2,
+            completion_date: day(5),
+            ..Project::example(1, "A", "Work", day(2))
+        };
+        let lines = unfolded(&calendar(
+            std::slice::from_ref(&project),
+            "Projects",
+            day(10),
+        ));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20260306".to_string()));
+        assert!(lines.contains(&"STATUS:CONFIRMED".to_string()));
+
+        // completion before the start never ends the event before it begins
+        let project = Project {
+            completion_date: day(1),
+            ..project
+        };
+        let lines = unfolded(&calendar(&[project], "Projects", day(10)));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20260303".to_string()));
+    }
+}
@@ -0,0 +1,745 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+
+use crate::models;
+use crate::validate;
+
+// project fields that can be mapped to a csv column
+pub const CSV_FIELDS: [&str; 7] = [
+    "name",
+    "category",
+    "status",
+    "notes",
+    "creation_date",
+    "start_date",
+    "completion_date",
+];
+
+#[derive(Debug)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug)]
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+// parse csv with a header row
+pub fn parse_csv(data: &str) -> anyhow::Result<CsvTable> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(data.trim_start_matches('\u{feff}').as_bytes());
+    let headers = reader
+        .headers()?
+        .iter()
+        .map(|header| header.trim().to_string())
+        .collect();
+    let rows = reader
+        .records()
+        .map(|record| record.map(|record| record.iter().map(|field| field.to_string()).collect()))
+        .collect::<Result<_, _>>()?;
+    Ok(CsvTable { headers, rows })
+}
+
+// guess the column for a field from the header names
+pub fn guess_column(headers: &[String], field: &str) -> Option<usize> {
+    headers
+        .iter()
+        .position(|header| header.to_lowercase().replace(' ', "_") == field)
+}
+
+// status names accepted on import
+pub fn parse_status(value: &str) -> Option<u64> {
+    match value.trim().to_lowercase().as_str() {
+        "" | "0" | "backlog" | "todo" | "to do" | "open" => Some(0),
+        "1" | "started" | "in progress" | "active" => Some(1),
+        "2" | "completed" | "complete" | "done" | "closed" => Some(2),
+        _ => None,
+    }
+}
+
+fn parse_date(value: &str, field: &str) -> Result<Option<NaiveDate>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| format!("{} '{}' is not a date (YYYY-MM-DD)", field, value))
+}
+
+// convert rows to projects using a column index per field, collecting one error per invalid row
+pub fn csv_projects(
+    table: &CsvTable,
+    mapping: &[(&str, Option<usize>)],
+    default_category: &str,
+    categories: &[&str],
+    today: NaiveDate,
+) -> (Vec<models::NewProject>, Vec<RowError>) {
+    let mut projects = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, row) in table.rows.iter().enumerate() {
+        let field = |name: &str| {
+            mapping
+                .iter()
+                .find(|(field, _)| *field == name)
+                .and_then(|(_, column)| *column)
+                .and_then(|column| row.get(column))
+                .map_or("", |value| value.as_str())
+        };
+
+        let result = (|| {
+            let name = validate::project_name(field("name"))?;
+            let category = match field("category").trim() {
+                "" => default_category,
+                category => category,
+            };
+            if !categories.contains(&category) {
+                return Err(format!("unknown category '{}'", category));
+            }
+            let status = parse_status(field("status"))
+                .ok_or_else(|| format!("unknown status '{}'", field("status")))?;
+            let creation_date =
+                parse_date(field("creation_date"), "creation_date")?.unwrap_or(today);
+            let start_date =
+                parse_date(field("start_date"), "start_date")?.unwrap_or(creation_date);
+            let completion_date =
+                parse_date(field("completion_date"), "completion_date")?.unwrap_or(today);
+            Ok(models::NewProject {
+                name: name.to_string(),
+                category: category.to_string(),
+                status,
+                notes: validate::notes(field("notes"))?.to_string(),
+                creation_date,
+                start_date,
+                completion_date,
+            })
+        })();
+
+        match result {
+            Ok(project) => projects.push(project),
+            // row numbers as shown in a spreadsheet, the header is row 1
+            Err(message) => errors.push(RowError {
+                row: index + 2,
+                message,
+            }),
+        }
+    }
+
+    (projects, errors)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Source {
+    Trello,
+    Todoist,
+    TaskWarrior,
+}
+
+impl Source {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Trello => "trello",
+            Source::Todoist => "todoist",
+            Source::TaskWarrior => "taskwarrior",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Source> {
+        match value {
+            "trello" => Some(Source::Trello),
+            "todoist" => Some(Source::Todoist),
+            "taskwarrior" => Some(Source::TaskWarrior),
+            _ => None,
+        }
+    }
+}
+
+// project read from another tool, category is chosen per list on import
+#[derive(Debug)]
+pub struct ImportedItem {
+    pub list: String,
+    pub project: models::NewProject,
+}
+
+#[derive(Deserialize, Debug)]
+struct TrelloBoard {
+    #[serde(default)]
+    lists: Vec<TrelloList>,
+    #[serde(default)]
+    cards: Vec<TrelloCard>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TrelloList {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct TrelloCard {
+    id: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    #[serde(rename = "idList")]
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(rename = "dueComplete", default)]
+    due_complete: bool,
+    start: Option<DateTime<Utc>>,
+    #[serde(rename = "dateLastActivity")]
+    date_last_activity: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TaskWarriorTask {
+    description: String,
+    status: String,
+    project: Option<String>,
+    entry: Option<String>,
+    start: Option<String>,
+    end: Option<String>,
+    #[serde(default)]
+    annotations: Vec<TaskWarriorAnnotation>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TaskWarriorAnnotation {
+    description: String,
+}
+
+// status of a project with optional start and completion
+fn status_of(started: bool, completed: bool) -> u64 {
+    if completed {
+        2
+    } else if started {
+        1
+    } else {
+        0
+    }
+}
+
+// trello card ids start with the creation time as hex seconds
+fn trello_creation_date(id: &str) -> Option<NaiveDate> {
+    let seconds = i64::from_str_radix(id.get(..8)?, 16).ok()?;
+    DateTime::from_timestamp(seconds, 0).map(|date| date.date_naive())
+}
+
+// items are numbered by their position in the export
+fn parse_trello(data: &str, today: NaiveDate) -> anyhow::Result<Vec<(usize, ImportedItem)>> {
+    let board: TrelloBoard = serde_json::from_str(data)?;
+    let items = board
+        .cards
+        .into_iter()
+        .enumerate()
+        .filter(|(_, card)| !card.closed)
+        .map(|(index, card)| {
+            let list = board
+                .lists
+                .iter()
+                .find(|list| list.id == card.id_list)
+                .map_or_else(|| "Trello".to_string(), |list| list.name.clone());
+            let list_done = ["done", "complete"]
+                .iter()
+                .any(|word| list.to_lowercase().contains(word));
+            let completed = card.due_complete || list_done;
+            let creation_date = trello_creation_date(&card.id).unwrap_or(today);
+            let start_date = card.start.map_or(creation_date, |date| date.date_naive());
+            let item = ImportedItem {
+                list,
+                project: models::NewProject {
+                    name: card.name,
+                    category: String::new(),
+                    status: status_of(card.start.is_some(), completed),
+                    notes: card.desc,
+                    creation_date,
+                    start_date,
+                    completion_date: card
+                        .date_last_activity
+                        .filter(|_| completed)
+                        .map_or(today, |date| date.date_naive()),
+                },
+            };
+            (index + 1, item)
+        })
+        .collect();
+    Ok(items)
+}
+
+// todoist csv exports one project per file, sections become lists
+fn parse_todoist(data: &str, today: NaiveDate) -> anyhow::Result<Vec<(usize, ImportedItem)>> {
+    let table = parse_csv(data)?;
+    let column = |name: &str| {
+        table
+            .headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name))
+    };
+    let (Some(kind), Some(content)) = (column("type"), column("content")) else {
+        anyhow::bail!("Todoist CSV needs TYPE and CONTENT columns");
+    };
+    let description = column("description");
+    let checked = column("checked").or(column("completed"));
+
+    let mut list = "Todoist".to_string();
+    let mut items = Vec::new();
+    for (index, row) in table.rows.iter().enumerate() {
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| row.get(index))
+                .map_or("", |value| value.as_str())
+        };
+        match field(Some(kind)).to_lowercase().as_str() {
+            "section" => list = field(Some(content)).to_string(),
+            "task" => {
+                let completed = matches!(field(checked).to_lowercase().as_str(), "1" | "true");
+                // row numbers as shown in a spreadsheet, the header is row 1
+                items.push((
+                    index + 2,
+                    ImportedItem {
+                        list: list.clone(),
+                        project: models::NewProject {
+                            name: field(Some(content)).to_string(),
+                            category: String::new(),
+                            status: status_of(false, completed),
+                            notes: field(description).to_string(),
+                            creation_date: today,
+                            start_date: today,
+                            completion_date: today,
+                        },
+                    },
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(items)
+}
+
+fn taskwarrior_date(value: &Option<String>) -> Option<NaiveDate> {
+    let value = value.as_deref()?;
+    chrono::NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
+        .map(|date| date.date())
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok())
+}
+
+// taskwarrior exports a json array, older versions one json object per line
+fn parse_taskwarrior(data: &str, today: NaiveDate) -> anyhow::Result<Vec<(usize, ImportedItem)>> {
+    let tasks: Vec<TaskWarriorTask> = match serde_json::from_str(data) {
+        Ok(tasks) => tasks,
+        Err(_) => data
+            .lines()
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| line.starts_with('{'))
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?,
+    };
+
+    let items = tasks
+        .into_iter()
+        .enumerate()
+        .filter(|(_, task)| task.status != "deleted")
+        .map(|(index, task)| {
+            let creation_date = taskwarrior_date(&task.entry).unwrap_or(today);
+            let start_date = taskwarrior_date(&task.start);
+            let completed = task.status == "completed";
+            let item = ImportedItem {
+                list: task
+                    .project
+                    .clone()
+                    .unwrap_or_else(|| "TaskWarrior".to_string()),
+                project: models::NewProject {
+                    name: task.description,
+                    category: String::new(),
+                    status: status_of(start_date.is_some(), completed),
+                    notes: task
+                        .annotations
+                        .into_iter()
+                        .map(|annotation| annotation.description)
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    creation_date,
+                    start_date: start_date.unwrap_or(creation_date),
+                    completion_date: taskwarrior_date(&task.end)
+                        .filter(|_| completed)
+                        .unwrap_or(today),
+                },
+            };
+            (index + 1, item)
+        })
+        .collect();
+    Ok(items)
+}
+
+// read an export file of another tool, items with an invalid name or notes are rejected with one error each
+pub fn parse_source(
+    source: Source,
+    data: &str,
+    today: NaiveDate,
+) -> anyhow::Result<(Vec<ImportedItem>, Vec<RowError>)> {
+    let data = data.trim_start_matches('\u{feff}');
+    let parsed = match source {
+        Source::Trello => parse_trello(data, today)?,
+        Source::Todoist => parse_todoist(data, today)?,
+        Source::TaskWarrior => parse_taskwarrior(data, today)?,
+    };
+
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    for (row, mut item) in parsed {
+        let result = (move || {
+            item.project.name = validate::project_name(&item.project.name)?.to_string();
+            validate::notes(&item.project.notes)?;
+            Ok(item)
+        })();
+        match result {
+            Ok(item) => items.push(item),
+            Err(message) => errors.push(RowError { row, message }),
+        }
+    }
+    Ok((items, errors))
+}
+
+// list names in order of first appearance with their number of items
+pub fn lists(items: &[ImportedItem]) -> Vec<(String, usize)> {
+    let mut lists: Vec<(String, usize)> = Vec::new();
+    for item in items {
+        match lists.iter_mut().find(|(name, _)| *name == item.list) {
+            Some((_, count)) => *count += 1,
+            None => lists.push((item.list.clone(), 1)),
+        }
+    }
+    lists
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 15).unwrap()
+    }
+
+    fn mapping(headers: &[String]) -> Vec<(&'static str, Option<usize>)> {
+        CSV_FIELDS
+            .iter()
+            .map(|field| (*field, guess_column(headers, field)))
+            .collect()
+    }
+
+    #[test]
+    fn parse_csv_strips_bom_and_trims_headers() {
+        let table =
+            parse_csv("\u{feff}Name , Category\r\nA,Work\r\n\"B, quoted\",Home\r\n").unwrap();
+        assert_eq!(table.headers, ["Name", "Category"]);
+        assert_eq!(table.rows, [["A", "Work"], ["B, quoted", "Home"]]);
+    }
+
+    #[test]
+    fn parse_csv_accepts_short_rows() {
+        let table = parse_csv("name,category,notes\nA\nB,Work,text\n").unwrap();
+        assert_eq!(table.rows[0], ["A"]);
+        assert_eq!(table.rows[1], ["B", "Work", "text"]);
+    }
+
+    #[test]
+    fn guess_column_ignores_case_and_spaces() {
+        let headers = ["Name".to_string(), "Start Date".to_string()];
+        assert_eq!(guess_column(&headers, "name"), Some(0));
+        assert_eq!(guess_column(&headers, "start_date"), Some(1));
+        assert_eq!(guess_column(&headers, "notes"), None);
+    }
+
+    #[test]
+    fn parse_status_accepts_names_and_numbers() {
+        assert_eq!(parse_status(""), Some(0));
+        assert_eq!(parse_status("To Do"), Some(0));
+        assert_eq!(parse_status(" in progress "), Some(1));
+        assert_eq!(parse_status("1"), Some(1));
+        assert_eq!(parse_status("DONE"), Some(2));
+        assert_eq!(parse_status("3"), None);
+        assert_eq!(parse_status("waiting"), None);
+    }
+
+    #[test]
+    fn csv_projects_fills_defaults() {
+        let table =
+            parse_csv("name,category,status,start_date\n  Garden  ,,started,2026-01-10\n").unwrap();
+        let (projects, errors) = csv_projects(
+            &table,
+            &mapping(&table.headers),
+            "Personal",
+            &["Personal"],
+            today(),
+        );
+        assert!(errors.is_empty());
+        let project = &projects[0];
+        assert_eq!(project.name, "Garden");
+        assert_eq!(project.category, "Personal");
+        assert_eq!(project.status, 1);
+        assert_eq!(project.notes, "");
+        assert_eq!(project.creation_date, today());
+        assert_eq!(
+            project.start_date,
+            NaiveDate::from_ymd_opt(2026, 1, 10).unwrap()
+        );
+        assert_eq!(project.completion_date, today());
+    }
+
+    #[test]
+    fn csv_projects_start_defaults_to_creation_date() {
+        let table = parse_csv("name,creation_date\nA,2025-12-01\n").unwrap();
+        let (projects, _) =
+            csv_projects(&table, &mapping(&table.headers), "Work", &["Work"], today());
+        let created = NaiveDate::from_ymd_opt(2025, 12, 1).unwrap();
+        assert_eq!(projects[0].creation_date, created);
+        assert_eq!(projects[0].start_date, created);
+    }
+
+    #[test]
+    fn csv_projects_reports_each_invalid_row() {
+        let data = "name,category,status,completion_date\n\
+                    Good,Work,done,2026-02-01\n\
+                    ,Work,,\n\
+                    Other,Unknown,,\n\
+                    Third,Work,waiting,\n\
+                    Fourth,Work,,01/02/2026\n";
+        let table = parse_csv(data).unwrap();
+        let (projects, errors) =
+            csv_projects(&table, &mapping(&table.headers), "Work", &["Work"], today());
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "Good");
+        let errors: Vec<_> = errors
+            .iter()
+            .map(|error| (error.row, error.message.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (3, "name is empty"),
+                (4, "unknown category 'Unknown'"),
+                (5, "unknown status 'waiting'"),
+                (6, "completion_date '01/02/2026' is not a date (YYYY-MM-DD)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_projects_ignores_unmapped_columns() {
+        let table = parse_csv("title,name\nIgnored,Used\n").unwrap();
+        let (projects, errors) = csv_projects(
+            &table,
+            &[("name", Some(1)), ("notes", None)],
+            "Work",
+            &["Work"],
+            today(),
+        );
+        assert!(errors.is_empty());
+        assert_eq!(projects[0].name, "Used");
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn rows(errors: &[RowError]) -> Vec<(usize, &str)> {
+        errors
+            .iter()
+            .map(|error| (error.row, error.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn source_names_round_trip() {
+        for source in [Source::Trello, Source::Todoist, Source::TaskWarrior] {
+            assert_eq!(Source::parse(source.as_str()), Some(source));
+        }
+        assert_eq!(Source::parse("asana"), None);
+    }
+
+    #[test]
+    fn trello_card_ids_carry_the_creation_date() {
+        assert_eq!(
+            trello_creation_date("5f5a0c00aaaaaaaaaaaaaaaa"),
+            Some(date(2020, 9, 10))
+        );
+        assert_eq!(trello_creation_date("zz"), None);
+        assert_eq!(trello_creation_date("not hex at all"), None);
+    }
+
+    #[test]
+    fn trello_maps_lists_and_skips_archived_cards() {
+        let data = r#"{
+            "lists": [{"id": "l1", "name": "Doing"}, {"id": "l2", "name": "Done!"}],
+            "cards": [
+                {"id": "5f5a0c00aaaaaaaaaaaaaaaa", "name": "Started", "desc": "notes",
+                 "idList": "l1", "start": "2020-09-12T08:00:00.000Z"},
+                {"id": "5f5a0c00bbbbbbbbbbbbbbbb", "name": "Archived", "idList": "l1", "closed": true},
+                {"id": "5f5a0c00cccccccccccccccc", "name": "Finished", "idList": "l2",
+                 "dateLastActivity": "2020-10-01T10:00:00.000Z"},
+                {"id": "x", "name": "Orphan", "idList": "gone", "dueComplete": true}
+            ]
+        }"#;
+        let (items, errors) = parse_source(Source::Trello, data, today()).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(items.len(), 3);
+
+        assert_eq!(items[0].list, "Doing");
+        assert_eq!(items[0].project.status, 1);
+        assert_eq!(items[0].project.notes, "notes");
+        assert_eq!(items[0].project.creation_date, date(2020, 9, 10));
+        assert_eq!(items[0].project.start_date, date(2020, 9, 12));
+
+        assert_eq!(items[1].list, "Done!");
+        assert_eq!(items[1].project.status, 2);
+        assert_eq!(items[1].project.start_date, date(2020, 9, 10));
+        assert_eq!(items[1].project.completion_date, date(2020, 10, 1));
+
+        // unknown list and id fall back to the default list and today
+        assert_eq!(items[2].list, "Trello");
+        assert_eq!(items[2].project.status, 2);
+        assert_eq!(items[2].project.creation_date, today());
+        assert_eq!(items[2].project.completion_date, today());
+    }
+
+    #[test]
+    fn trello_rejects_invalid_json() {
+        assert!(parse_source(Source::Trello, "name,list\n", today()).is_err());
+    }
+
+    #[test]
+    fn todoist_sections_become_lists() {
+        let data = "\u{feff}TYPE,CONTENT,DESCRIPTION,PRIORITY,CHECKED\n\
+                    task,Loose task,,1,false\n\
+                    section,Errands,,,\n\
+                    task,Buy paint,white,4,true\n\
+                    ,,,,\n\
+                    task,Call plumber,,1,0\n";
+        let (items, errors) = parse_source(Source::Todoist, data, today()).unwrap();
+        assert!(errors.is_empty());
+        let summary: Vec<_> = items
+            .iter()
+            .map(|item| {
+                (
+                    item.list.as_str(),
+                    item.project.name.as_str(),
+                    item.project.status,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Todoist", "Loose task", 0),
+                ("Errands", "Buy paint", 2),
+                ("Errands", "Call plumber", 0),
+            ]
+        );
+        assert_eq!(items[1].project.notes, "white");
+        assert_eq!(items[1].project.creation_date, today());
+    }
+
+    #[test]
+    fn todoist_needs_type_and_content() {
+        let error = parse_source(Source::Todoist, "name,notes\nA,B\n", today()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Todoist CSV needs TYPE and CONTENT columns"
+        );
+    }
+
+    #[test]
+    fn todoist_reports_spreadsheet_rows() {
+        let data = "TYPE,CONTENT\nsection,List\ntask,   \ntask,Fine\n";
+        let (items, errors) = parse_source(Source::Todoist, data, today()).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(rows(&errors), [(3, "name is empty")]);
+    }
+
+    #[test]
+    fn taskwarrior_reads_the_json_array() {
+        let data = r#"[
+            {"description": "Write report", "status": "pending", "project": "Work",
+             "entry": "20260101T090000Z", "start": "20260105T090000Z",
+             "annotations": [{"description": "first"}, {"description": "second"}]},
+            {"description": "Removed", "status": "deleted"},
+            {"description": "Shipped", "status": "completed",
+             "entry": "2026-01-02T09:00:00Z", "end": "20260201T120000Z"},
+            {"description": "Someday", "status": "waiting", "entry": "garbage"}
+        ]"#;
+        let (items, errors) = parse_source(Source::TaskWarrior, data, today()).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(items.len(), 3);
+
+        assert_eq!(items[0].list, "Work");
+        assert_eq!(items[0].project.status, 1);
+        assert_eq!(items[0].project.notes, "first\nsecond");
+        assert_eq!(items[0].project.creation_date, date(2026, 1, 1));
+        assert_eq!(items[0].project.start_date, date(2026, 1, 5));
+        assert_eq!(items[0].project.completion_date, today());
+
+        assert_eq!(items[1].list, "TaskWarrior");
+        assert_eq!(items[1].project.status, 2);
+        assert_eq!(items[1].project.creation_date, date(2026, 1, 2));
+        assert_eq!(items[1].project.start_date, date(2026, 1, 2));
+        assert_eq!(items[1].project.completion_date, date(2026, 2, 1));
+
+        assert_eq!(items[2].project.status, 0);
+        assert_eq!(items[2].project.creation_date, today());
+    }
+
+    #[test]
+    fn taskwarrior_reads_one_object_per_line() {
+        let data = "{\"description\":\"One\",\"status\":\"pending\"},\n\
+                    \n\
+                    {\"description\":\"Two\",\"status\":\"completed\"}\n";
+        let (items, _) = parse_source(Source::TaskWarrior, data, today()).unwrap();
+        let names: Vec<_> = items
+            .iter()
+            .map(|item| item.project.name.as_str())
+            .collect();
+        assert_eq!(names, ["One", "Two"]);
+        assert!(parse_source(Source::TaskWarrior, "{\"status\":1}\n", today()).is_err());
+    }
+
+    #[test]
+    fn invalid_items_are_reported_and_names_trimmed() {
+        let long = "x".repeat(validate::MAX_NAME_LENGTH + 1);
+        let notes = "n".repeat(validate::MAX_NOTES_LENGTH + 1);
+        let data = serde_json::json!([
+            {"description": "  Kept  ", "status": "pending"},
+            {"description": "gone", "status": "deleted"},
+            {"description": long, "status": "pending"},
+            {"description": "Notes", "status": "pending",
+             "annotations": [{"description": notes}]},
+        ])
+        .to_string();
+        let (items, errors) = parse_source(Source::TaskWarrior, &data, today()).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].project.name, "Kept");
+        assert_eq!(
+            rows(&errors),
+            [
+                (3, "name is longer than 200 characters"),
+                (4, "notes are longer than 10000 characters"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_count_items_in_order_of_appearance() {
+        let data = "TYPE,CONTENT\ntask,A\nsection,B\ntask,B1\ntask,B2\n";
+        let (items, _) = parse_source(Source::Todoist, data, today()).unwrap();
+        assert_eq!(
+            lists(&items),
+            [("Todoist".to_string(), 1), ("B".to_string(), 2)]
+        );
+    }
+}
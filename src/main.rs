@@ -1,52 +1,445 @@
+use axum::extract::DefaultBodyLimit;
+use axum::middleware;
+use axum::response::Redirect;
 use axum::routing::{get, post};
 use axum::Router;
 use dotenv::dotenv;
-use sqlx::mysql::MySqlPool;
 use std::env;
-use tower_http::services::ServeDir;
+use std::io::BufRead;
 
+mod assets;
+mod auth;
+mod config;
+mod csrf;
 mod db;
 mod error;
+mod export;
+mod feed;
 mod handlers;
+mod ical;
+mod import;
 mod models;
+mod report;
+mod state;
+mod timeline;
+mod urls;
+mod validate;
+mod webhooks;
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<()> {
     println!("Server Started");
     // read .env file
     dotenv().ok();
 
-    // connect to mysql database
-    let database_url = format!("mysql://{}:{}@{}:{}/{}", &env::var("DB_USERNAME")?, &env::var("DB_PASSWORD")?, &env::var("DB_HOST")?, &env::var("DB_PORT")?, &env::var("DB_NAME")?);
-    let pool = MySqlPool::connect(&database_url).await?;
-    println!("Connected to database: {}", database_url);
+    // read config.toml (or CONFIG_FILE) and the environment, refuses to start on invalid settings
+    let config = config::Config::load()?;
+    // dates are formatted in local time, chrono reads the time zone from TZ,
+    // it is set before the runtime starts its threads because changing the environment is not thread safe
+    if let Some(timezone) = &config.timezone {
+        env::set_var("TZ", timezone);
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run(config))
+}
+
+async fn run(config: config::Config) -> anyhow::Result<()> {
+    config.report();
+    // templates and redirects prefix their urls with the base path
+    urls::set_base_path(&config.base_path);
+
+    // connect to the database, a sqlite: url runs without a database server
+    let database_url = config.database.url().expect("database url is validated");
+    let db = db::connect(&database_url).await?;
+    println!("Connected to database");
+
+    // bring the schema up to date, refuses to start if the database is newer than this binary
+    db.migrate().await?;
+
+    // `project-tracker --migrate-only` exits after migrating, e.g. to migrate before a rolling deploy
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some("--migrate-only") {
+        println!("Migrated database");
+        return Ok(());
+    }
+
+    // `project-tracker create-user <username>` creates an account (or resets its password)
+    // with the password read from stdin, then exits
+    if args.get(1).map(String::as_str) == Some("create-user") {
+        return create_user(
+            &db,
+            args.get(2).map(String::as_str),
+            &config.default_categories,
+        )
+        .await;
+    }
+
+    // start webhook delivery queue
+    let webhooks = config
+        .features
+        .webhooks
+        .then(|| webhooks::spawn(db.clone(), config.webhooks.clone()));
+    let state = state::AppState {
+        db,
+        page_size: config.page_size,
+        secure_cookies: config.secure_cookies,
+        default_categories: config.default_categories,
+        backup_dir: config.backup_dir,
+        features: config.features,
+        webhook_config: config.webhooks,
+        webhooks,
+        changes: tokio::sync::broadcast::channel(100).0,
+    };
+
+    let app = router(state, &config.base_path);
+
+    // set up listener
+    let listener = tokio::net::TcpListener::bind(&config.bind_address).await?;
+    println!(
+        "Listening at: {}{}",
+        &config.bind_address, &config.base_path
+    );
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+// routes of the app, mounted below the base path if there is one
+fn router(state: state::AppState, base_path: &str) -> Router {
+    // uploads and import confirmations carrying them may be larger than the default body limit of 2 MB,
+    // but not unbounded
+    let upload_limit = DefaultBodyLimit::max(validate::MAX_UPLOAD_SIZE);
 
     // set up router
-    let app = Router::new()
+    let mut app = Router::new()
         .route("/", get(handlers::index_handler))
         .route("/start", post(handlers::start_handler))
         .route("/complete", post(handlers::complete_handler))
         .route("/completed", get(handlers::completed_handler))
+        .route("/timeline", get(handlers::timeline_handler))
+        .route("/timeline.svg", get(handlers::timeline_svg_handler))
+        .route("/stats", get(handlers::stats_handler))
+        .route("/report", get(handlers::report_handler))
+        .route("/list/{category_id}", get(handlers::list_handler))
+        .route("/events", get(handlers::events_handler))
         .route("/add", post(handlers::add_handler))
         .route("/delete", post(handlers::delete_handler))
         .route("/up", post(handlers::up_handler))
         .route("/down", post(handlers::down_handler))
         .route("/update_notes", post(handlers::update_notes_handler))
-        .route("/:id", get(handlers::project_handler))
+        .route("/{id}", get(handlers::project_handler))
         .route("/backup", get(handlers::backup_handler))
         .route("/upload", get(handlers::upload_handler))
-        .route("/restore", post(handlers::restore_handler))
-        .nest_service("/css", ServeDir::new("css"))
-        .nest_service("/assets", ServeDir::new("assets"))
-        .nest_service("/js", ServeDir::new("js"))
-        .nest_service("/node_modules", ServeDir::new("node_modules"))
-        .with_state(pool);
+        .route(
+            "/restore",
+            post(handlers::restore_handler).layer(upload_limit),
+        )
+        .route("/export", get(handlers::export_handler))
+        .route("/export.csv", get(handlers::csv_export_handler))
+        .route("/export.md", get(handlers::markdown_export_handler))
+        .route("/export.txt", get(handlers::todo_export_handler))
+        .route("/categories", get(handlers::categories_handler))
+        .route("/categories/add", post(handlers::add_category_handler))
+        .route(
+            "/categories/delete",
+            post(handlers::delete_category_handler),
+        )
+        .route("/categories/share", post(handlers::share_handler))
+        .route("/categories/unshare", post(handlers::unshare_handler))
+        .route("/logout", post(handlers::logout_handler));
+    // optional features, disabled ones are not routed at all
+    let features = state.features;
+    if features.calendar {
+        app = app.route("/calendar.ics", get(handlers::calendar_handler));
+    }
+    // calendar and atom feeds share the feed token of a user
+    if features.calendar || features.feeds {
+        app = app.route("/calendar/token", post(handlers::calendar_token_handler));
+    }
+    if features.feeds {
+        app = app
+            .route(
+                "/feed/completed.atom",
+                get(handlers::completed_feed_handler),
+            )
+            .route("/feed/activity.atom", get(handlers::activity_feed_handler));
+    }
+    if features.webhooks {
+        app = app
+            .route("/webhooks", get(handlers::webhooks_handler))
+            .route("/webhooks/add", post(handlers::add_webhook_handler))
+            .route("/webhooks/delete", post(handlers::delete_webhook_handler))
+            .route("/webhooks/deliveries", get(handlers::deliveries_handler));
+    }
+    if features.api_tokens {
+        app = app
+            .route("/tokens", get(handlers::tokens_handler))
+            .route("/tokens/add", post(handlers::add_token_handler))
+            .route("/tokens/delete", post(handlers::delete_token_handler));
+    }
+    if features.import {
+        app = app
+            .route("/import", get(handlers::import_handler))
+            .route(
+                "/import/csv",
+                post(handlers::csv_import_handler).layer(upload_limit),
+            )
+            .route(
+                "/import/csv/confirm",
+                post(handlers::csv_import_confirm_handler).layer(upload_limit),
+            )
+            .route(
+                "/import/tool",
+                post(handlers::tool_import_handler).layer(upload_limit),
+            )
+            .route(
+                "/import/tool/confirm",
+                post(handlers::tool_import_confirm_handler).layer(upload_limit),
+            );
+    }
+    let app = app
+        // every route above requires a signed in user or an api token
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_login,
+        ))
+        .route(
+            "/login",
+            get(handlers::login_handler).post(handlers::login_submit_handler),
+        )
+        .route(
+            "/setup",
+            get(handlers::setup_handler).post(handlers::setup_submit_handler),
+        )
+        // css, scripts and fonts are compiled into the binary
+        .route("/static/{*path}", get(handlers::asset_handler))
+        .fallback(handlers::fallback_handler)
+        // render errors as pages, or as fragments for htmx requests
+        .layer(middleware::from_fn(error::render_errors))
+        .with_state(state);
+    // behind a reverse proxy the app is mounted below the base path, e.g. /projects/
+    if base_path.is_empty() {
+        app
+    } else {
+        // nesting only matches /projects, proxies and bookmarks may add the trailing slash
+        let index = urls::url("/");
+        Router::new().nest(base_path, app).route(
+            &format!("{}/", base_path),
+            get(|| async move { Redirect::permanent(&index) }),
+        )
+    }
+}
 
-    // set up listener
-    let address = "0.0.0.0:4200";
-    let listener = tokio::net::TcpListener::bind(&address).await?;
-    println!("Listening at: {}", &address);
-    axum::serve(listener, app).await?;
+// create a user from the command line, or reset the password of an existing one
+async fn create_user(
+    db: &db::Db,
+    username: Option<&str>,
+    categories: &[String],
+) -> anyhow::Result<()> {
+    let Some(username) = username
+        .map(str::trim)
+        .filter(|username| !username.is_empty())
+    else {
+        anyhow::bail!("Usage: project-tracker create-user <username>");
+    };
+
+    println!("Password for {} (read from stdin):", username);
+    let mut password = String::new();
+    std::io::stdin().lock().read_line(&mut password)?;
+    let password = password.trim_end_matches(['\r', '\n']);
+    if password.chars().count() < auth::MIN_PASSWORD_LENGTH {
+        anyhow::bail!(
+            "Password needs at least {} characters",
+            auth::MIN_PASSWORD_LENGTH
+        );
+    }
+
+    let password_hash = auth::hash_password(password)?;
+    match db.get_user_by_username(username).await? {
+        Some(user) => {
+            db.update_password(user.id, &password_hash).await?;
+            println!("Updated password of {}", username);
+        }
+        None => {
+            db.add_user(username, &password_hash, categories).await?;
+            println!("Created user {}", username);
+        }
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+
+    // serve the router on a local port, the address is returned without a trailing slash
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        address
+    }
+
+    fn client() -> reqwest::Client {
+        reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn router_builds_with_every_feature() {
+        let _ = router(state::test_state().await, "");
+        let _ = router(state::test_state().await, "/projects");
+    }
+
+    #[tokio::test]
+    async fn router_serves_assets_pages_and_redirects() {
+        let address = serve(router(state::test_state().await, "")).await;
+        let client = client();
+        let status = |path: &str| {
+            let request = client.get(format!("{}{}", address, path));
+            async move { request.send().await.unwrap().status() }
+        };
+
+        // assets below a directory need the wildcard to match more than one segment
+        let asset = assets::ASSETS
+            .iter()
+            .find(|asset| asset.path.contains('/'))
+            .unwrap();
+        assert_eq!(
+            status(&format!("/static/{}", asset.path)).await,
+            StatusCode::OK
+        );
+        assert_eq!(status("/static/js/missing.js").await, StatusCode::NOT_FOUND);
+        assert_eq!(status("/setup").await, StatusCode::OK);
+        // project and list pages need a signed in user
+        for path in ["/", "/1", "/list/1", "/timeline"] {
+            assert_eq!(status(path).await, StatusCode::SEE_OTHER, "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn mounted_router_serves_below_the_base_path() {
+        let address = serve(router(state::test_state().await, "/projects")).await;
+        let client = client();
+        let response = client
+            .get(format!("{}/projects/setup", address))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = client
+            .get(format!("{}/setup", address))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = client
+            .get(format!("{}/projects/", address))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    }
+
+    #[tokio::test]
+    async fn feeds_are_read_with_the_feed_token() {
+        let state = state::test_state().await;
+        let user_id = state.db.add_user("alice", "hash", &[]).await.unwrap();
+        let token = auth::calendar_token(user_id);
+        state
+            .db
+            .set_calendar_token_hash(user_id, &auth::token_hash(&token))
+            .await
+            .unwrap();
+        let address = serve(router(state, "")).await;
+        let client = client();
+        let get = |path: String| {
+            let request = client.get(format!("{}{}", address, path));
+            async move { request.send().await.unwrap() }
+        };
+
+        for feed in [
+            "/calendar.ics",
+            "/feed/completed.atom",
+            "/feed/activity.atom",
+        ] {
+            let response = get(format!("{}?token={}", feed, token)).await;
+            assert_eq!(response.status(), StatusCode::OK, "{}", feed);
+            let response = get(format!("{}?token={}x", feed, token)).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", feed);
+            let response = get(feed.to_string()).await;
+            assert_eq!(response.status(), StatusCode::SEE_OTHER, "{}", feed);
+        }
+        // the token only opens the feeds
+        for path in ["/", "/timeline", "/backup", "/export.csv"] {
+            let response = get(format!("{}?token={}", path, token)).await;
+            assert_eq!(response.status(), StatusCode::SEE_OTHER, "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn api_tokens_open_the_routes_of_their_scope() {
+        for base_path in ["", "/projects"] {
+            let state = state::test_state().await;
+            let categories = ["Work".to_string()];
+            let user_id = state
+                .db
+                .add_user("alice", "hash", &categories)
+                .await
+                .unwrap();
+            for scope in ["read", "write", "admin"] {
+                let token_hash = auth::token_hash(scope);
+                state
+                    .db
+                    .add_api_token(user_id, scope, &token_hash, scope)
+                    .await
+                    .unwrap();
+            }
+            let address = serve(router(state, base_path)).await;
+            let client = client();
+            let status = |method: &str, path: &str, token: &str| {
+                let url = format!("{}{}{}", address, base_path, path);
+                let request = match method {
+                    "GET" => client.get(url),
+                    _ => client
+                        .post(url)
+                        .form(&[("category_id", "1"), ("name", "Report")]),
+                };
+                let request = request.bearer_auth(token);
+                async move { request.send().await.unwrap().status() }
+            };
+
+            assert_eq!(status("GET", "/export.csv", "read").await, StatusCode::OK);
+            assert_eq!(status("GET", "/list/1", "read").await, StatusCode::OK);
+            assert_eq!(status("POST", "/add", "read").await, StatusCode::FORBIDDEN);
+            assert_eq!(status("POST", "/add", "write").await, StatusCode::OK);
+            assert_eq!(
+                status("GET", "/backup", "write").await,
+                StatusCode::FORBIDDEN
+            );
+            assert_eq!(status("GET", "/backup", "admin").await, StatusCode::OK);
+            assert_eq!(
+                status("GET", "/tokens", "write").await,
+                StatusCode::FORBIDDEN
+            );
+            assert_eq!(
+                status("POST", "/restore", "write").await,
+                StatusCode::FORBIDDEN
+            );
+            assert_eq!(
+                status("POST", "/tokens/add", "write").await,
+                StatusCode::FORBIDDEN
+            );
+            assert_eq!(
+                status("GET", "/timeline", "unknown").await,
+                StatusCode::UNAUTHORIZED
+            );
+        }
+    }
+}
@@ -1,16 +1,255 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
-#[derive(FromRow, Deserialize, Serialize, Debug)]
+// postgres has no unsigned integers, u64 columns are read as i64 and converted
+#[derive(FromRow, Deserialize, Serialize, Debug, Clone)]
 pub struct Project {
+    #[sqlx(try_from = "i64")]
     pub id: u64,
     pub name: String,
     pub category: String,
+    #[sqlx(try_from = "i64")]
     pub position: u64,
+    #[sqlx(try_from = "i64")]
     pub status: u64,
     pub notes: String,
     pub creation_date: NaiveDate,
     pub start_date: NaiveDate,
     pub completion_date: NaiveDate,
+    // incremented on every write but position shifts, backups without it restore as version 1
+    #[serde(default)]
+    #[sqlx(try_from = "i64")]
+    pub version: u64,
+    #[serde(default)]
+    #[sqlx(try_from = "i64")]
+    pub owner_id: u64,
+}
+
+// project for tests, active since the given date with the other fields at defaults
+#[cfg(test)]
+impl Project {
+    pub fn example(id: u64, name: &str, category: &str, date: NaiveDate) -> Project {
+        Project {
+            id,
+            name: name.to_string(),
+            category: category.to_string(),
+            position: id,
+            status: 0,
+            notes: String::new(),
+            creation_date: date,
+            start_date: date,
+            completion_date: date,
+            version: 1,
+            owner_id: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewProject {
+    pub name: String,
+    pub category: String,
+    pub status: u64,
+    pub notes: String,
+    pub creation_date: NaiveDate,
+    pub start_date: NaiveDate,
+    pub completion_date: NaiveDate,
+}
+
+#[derive(Debug)]
+pub struct CompletedPage {
+    pub projects: Vec<Project>,
+    pub total: u64,
+    pub has_more: bool,
+}
+
+#[derive(FromRow, Debug)]
+pub struct CompletedPerMonth {
+    pub month: Option<String>,
+    pub category: String,
+    pub completed: i64,
+}
+
+#[derive(FromRow, Debug, Default)]
+pub struct DurationStats {
+    pub cycle_median: Option<i64>,
+    pub cycle_p90: Option<i64>,
+    pub lead_median: Option<i64>,
+    pub lead_p90: Option<i64>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct CategoryCount {
+    pub category: String,
+    pub total: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AgeBucket {
+    pub bucket: i64,
+    pub total: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct Activity {
+    #[sqlx(try_from = "i64")]
+    pub id: u64,
+    #[sqlx(try_from = "i64")]
+    pub project_id: u64,
+    pub project_name: String,
+    pub category: String,
+    pub event: String,
+    pub created_at: NaiveDateTime,
+}
+
+// project lifecycle event sent to listeners
+#[derive(Serialize, Debug, Clone)]
+pub struct ProjectEvent {
+    pub event: String,
+    pub timestamp: DateTime<Utc>,
+    pub project: Project,
+}
+
+#[derive(FromRow, Debug)]
+pub struct Webhook {
+    #[sqlx(try_from = "i64")]
+    pub id: u64,
+    pub url: String,
+    pub secret: String,
+    pub events: String,
+    pub creation_date: NaiveDate,
+}
+
+#[derive(FromRow, Debug)]
+pub struct WebhookDelivery {
+    pub id: u64,
+    pub webhook_id: u64,
+    pub event: String,
+    pub attempt: u64,
+    pub status_code: Option<u64>,
+    pub error: String,
+    pub delivered_at: NaiveDateTime,
+}
+
+impl WebhookDelivery {
+    // delivery got a 2xx response
+    pub fn succeeded(&self) -> bool {
+        self.status_code
+            .is_some_and(|status_code| (200..300).contains(&status_code))
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct User {
+    #[sqlx(try_from = "i64")]
+    pub id: u64,
+    pub username: String,
+    pub password_hash: String,
+}
+
+// user as shown to other users, without the password hash
+#[derive(FromRow, Debug, Clone)]
+pub struct UserName {
+    #[sqlx(try_from = "i64")]
+    pub id: u64,
+    pub username: String,
+}
+
+// category of a user, projects reference it by owner and name
+#[derive(FromRow, Debug, Clone)]
+pub struct Category {
+    #[sqlx(try_from = "i64")]
+    pub id: u64,
+    #[sqlx(try_from = "i64")]
+    pub owner_id: u64,
+    pub name: String,
+}
+
+#[derive(FromRow, Debug)]
+pub struct CategoryShare {
+    #[sqlx(try_from = "i64")]
+    pub id: u64,
+    #[sqlx(try_from = "i64")]
+    pub category_id: u64,
+    #[sqlx(try_from = "i64")]
+    pub user_id: u64,
+    pub access: String,
+}
+
+// what a user may do with the projects of a category
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Access {
+    Read,
+    Edit,
+}
+
+impl Access {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Access::Read => "read",
+            Access::Edit => "edit",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Access> {
+        match value {
+            "read" => Some(Access::Read),
+            "edit" => Some(Access::Edit),
+            _ => None,
+        }
+    }
+}
+
+// personal api token, only the hash of the token is stored
+#[derive(FromRow, Debug)]
+pub struct ApiToken {
+    #[sqlx(try_from = "i64")]
+    pub id: u64,
+    #[sqlx(try_from = "i64")]
+    pub user_id: u64,
+    pub name: String,
+    // comma separated scopes
+    pub scopes: String,
+    pub created_at: NaiveDateTime,
+    pub last_used: Option<NaiveDateTime>,
+}
+
+impl ApiToken {
+    // granted scopes include the ones below them, admin can also write and read
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes
+            .split(',')
+            .filter_map(Scope::parse)
+            .any(|granted| granted >= scope)
+    }
+}
+
+// what an api token may be used for
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Scope {
+    Read,
+    Write,
+    Admin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 3] = [Scope::Read, Scope::Write, Scope::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Scope> {
+        match value {
+            "read" => Some(Scope::Read),
+            "write" => Some(Scope::Write),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,245 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::models;
+
+const EXCERPT_LENGTH: usize = 160;
+
+#[derive(Debug)]
+pub struct ReportEntry {
+    pub id: u64,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub completion_date: NaiveDate,
+    pub duration: i64,
+    pub excerpt: String,
+}
+
+#[derive(Debug)]
+pub struct MonthSection {
+    pub name: String,
+    pub entries: Vec<ReportEntry>,
+}
+
+#[derive(Debug)]
+pub struct CategorySection {
+    pub name: String,
+    pub total: usize,
+    pub months: Vec<MonthSection>,
+}
+
+#[derive(Debug)]
+pub struct YearReport {
+    pub year: i32,
+    pub total: usize,
+    pub total_duration: i64,
+    pub average_duration: i64,
+    pub longest: Option<ReportEntry>,
+    pub busiest_month: Option<(String, usize)>,
+    pub categories: Vec<CategorySection>,
+}
+
+// first characters of the notes on a single line
+fn excerpt(notes: &str) -> String {
+    let text = notes.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > EXCERPT_LENGTH {
+        let cut: String = text.chars().take(EXCERPT_LENGTH).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        text
+    }
+}
+
+fn entry(project: &models::Project) -> ReportEntry {
+    ReportEntry {
+        id: project.id,
+        name: project.name.clone(),
+        start_date: project.start_date,
+        completion_date: project.completion_date,
+        duration: (project.completion_date - project.start_date).num_days(),
+        excerpt: excerpt(&project.notes),
+    }
+}
+
+fn month_name(month: u32) -> String {
+    NaiveDate::from_ymd_opt(2000, month, 1)
+        .unwrap()
+        .format("%B")
+        .to_string()
+}
+
+// build year in review from the projects completed in that year, categories in the given order first
+pub fn build(year: i32, projects: &[models::Project], categories: &[&str]) -> YearReport {
+    let total_duration: i64 = projects
+        .iter()
+        .map(|project| (project.completion_date - project.start_date).num_days())
+        .sum();
+
+    let longest = projects
+        .iter()
+        .max_by_key(|project| (project.completion_date - project.start_date).num_days())
+        .map(entry);
+
+    // month with the most completions, earliest month wins ties
+    let busiest_month = (1..=12)
+        .map(|month| {
+            let count = projects
+                .iter()
+                .filter(|project| project.completion_date.month() == month)
+                .count();
+            (month, count)
+        })
+        .filter(|(_, count)| *count > 0)
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(month, count)| (month_name(month), count));
+
+    let mut names: Vec<String> = categories.iter().map(|name| name.to_string()).collect();
+    for project in projects {
+        if !names.contains(&project.category) {
+            names.push(project.category.clone());
+        }
+    }
+
+    let categories = names
+        .into_iter()
+        .map(|name| {
+            let in_category: Vec<&models::Project> = projects
+                .iter()
+                .filter(|project| project.category == name)
+                .collect();
+            let months = (1..=12)
+                .map(|month| MonthSection {
+                    name: month_name(month),
+                    entries: in_category
+                        .iter()
+                        .filter(|project| project.completion_date.month() == month)
+                        .map(|project| entry(project))
+                        .collect(),
+                })
+                .filter(|section| !section.entries.is_empty())
+                .collect();
+            CategorySection {
+                total: in_category.len(),
+                name,
+                months,
+            }
+        })
+        .collect();
+
+    YearReport {
+        year,
+        total: projects.len(),
+        total_duration,
+        average_duration: if projects.is_empty() {
+            0
+        } else {
+            total_duration / projects.len() as i64
+        },
+        longest,
+        busiest_month,
+        categories,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    // project of a category started on a date and completed on another
+    fn completed(
+        id: u64,
+        category: &str,
+        start: NaiveDate,
+        completion: NaiveDate,
+    ) -> models::Project {
+        let mut project = models::Project::example(id, &format!("Project {}", id), category, start);
+        project.status = 2;
+        project.completion_date = completion;
+        project
+    }
+
+    fn months(section: &CategorySection) -> Vec<(&str, Vec<u64>)> {
+        section
+            .months
+            .iter()
+            .map(|month| {
+                let ids = month.entries.iter().map(|entry| entry.id).collect();
+                (month.name.as_str(), ids)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn projects_are_grouped_by_completion_month() {
+        let projects = [
+            completed(1, "Work", date(1, 1), date(1, 1)),
+            completed(2, "Work", date(1, 10), date(1, 31)),
+            completed(3, "Work", date(1, 20), date(2, 1)),
+            completed(4, "Work", date(11, 1), date(12, 31)),
+        ];
+        let report = build(2026, &projects, &["Work"]);
+        assert_eq!(
+            months(&report.categories[0]),
+            [
+                ("January", vec![1, 2]),
+                ("February", vec![3]),
+                ("December", vec![4])
+            ]
+        );
+        // the busiest month counts completions, not starts
+        assert_eq!(report.busiest_month, Some(("January".to_string(), 2)));
+    }
+
+    #[test]
+    fn categories_are_counted_in_the_given_order() {
+        let projects = [
+            completed(1, "Home", date(3, 1), date(3, 4)),
+            completed(2, "Work", date(3, 1), date(3, 11)),
+            completed(3, "Home", date(4, 1), date(4, 2)),
+            completed(4, "Garden", date(5, 1), date(5, 1)),
+        ];
+        let report = build(2026, &projects, &["Work", "Home", "Music"]);
+        let totals: Vec<_> = report
+            .categories
+            .iter()
+            .map(|section| (section.name.as_str(), section.total))
+            .collect();
+        // categories without projects stay listed, deleted ones still show up at the end
+        assert_eq!(
+            totals,
+            [("Work", 1), ("Home", 2), ("Music", 0), ("Garden", 1)]
+        );
+        assert_eq!(report.total, 4);
+        assert_eq!(report.total_duration, 3 + 10 + 1);
+        assert_eq!(report.average_duration, 14 / 4);
+        assert_eq!(report.longest.map(|entry| entry.id), Some(2));
+        // ties go to the earliest month
+        assert_eq!(report.busiest_month, Some(("March".to_string(), 2)));
+    }
+
+    #[test]
+    fn empty_year_has_no_highlights() {
+        let report = build(2026, &[], &["Work"]);
+        assert_eq!(report.total, 0);
+        assert_eq!(report.average_duration, 0);
+        assert!(report.longest.is_none());
+        assert!(report.busiest_month.is_none());
+        assert_eq!(report.categories[0].total, 0);
+        assert!(report.categories[0].months.is_empty());
+    }
+
+    #[test]
+    fn notes_are_shortened_to_one_line() {
+        assert_eq!(
+            excerpt("first line\n\n  second   line "),
+            "first line second line"
+        );
+        let long = "word ".repeat(60);
+        let short = excerpt(&long);
+        assert!(short.ends_with('…'));
+        assert_eq!(short.chars().count(), EXCERPT_LENGTH);
+    }
+}
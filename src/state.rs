@@ -0,0 +1,75 @@
+use axum_macros::FromRef;
+use chrono::{Local, Utc};
+use std::path::PathBuf;
+use tokio::sync::broadcast;
+
+use crate::config;
+use crate::db;
+use crate::models;
+use crate::webhooks;
+
+// shared application state, handlers can extract single fields via `State<T>`
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub db: db::Db,
+    pub page_size: u64,
+    // only send the session cookie over https
+    pub secure_cookies: bool,
+    // categories created for new users
+    pub default_categories: Vec<String>,
+    // projects are saved here before a restore replaces them
+    pub backup_dir: Option<PathBuf>,
+    pub features: config::Features,
+    // webhook targets allowed besides public hosts
+    pub webhook_config: config::WebhookConfig,
+    // not running when webhooks are disabled
+    pub webhooks: Option<webhooks::Dispatcher>,
+    // ids of categories whose project list changed, streamed to open pages
+    pub changes: broadcast::Sender<u64>,
+}
+
+impl AppState {
+    // notify listeners about a project lifecycle event
+    pub async fn publish(&self, event: &str, project: models::Project) {
+        self.notify(project.owner_id, &project.category).await;
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.send(models::ProjectEvent {
+                event: event.to_string(),
+                timestamp: Utc::now(),
+                project,
+            });
+        }
+    }
+
+    // tell open pages to reload the project list of a category
+    pub async fn notify(&self, owner_id: u64, category: &str) {
+        match self.db.get_category_by_name(owner_id, category).await {
+            // sending only fails if no page is listening
+            Ok(Some(category)) => {
+                let _ = self.changes.send(category.id);
+            }
+            Ok(None) => {}
+            Err(err) => println!(
+                "{} - Events - could not load category: {}",
+                Local::now(),
+                err
+            ),
+        }
+    }
+}
+
+// state on an empty, migrated sqlite database with the default settings, for tests
+#[cfg(test)]
+pub async fn test_state() -> AppState {
+    AppState {
+        db: db::test_db().await,
+        page_size: 20,
+        secure_cookies: false,
+        default_categories: Vec::new(),
+        backup_dir: None,
+        features: config::Features::default(),
+        webhook_config: config::WebhookConfig::default(),
+        webhooks: None,
+        changes: broadcast::channel(100).0,
+    }
+}
@@ -0,0 +1,507 @@
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::models;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Bar {
+    pub id: u64,
+    pub name: String,
+    pub category: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub in_progress: bool,
+    // position and size in percent of the timeline width
+    pub offset: f64,
+    pub width: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Timeline {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub granularity: Granularity,
+    pub ticks: Vec<NaiveDate>,
+    pub bars: Vec<Bar>,
+}
+
+// first day of the period containing date
+fn period_start(date: NaiveDate, granularity: Granularity) -> NaiveDate {
+    match granularity {
+        Granularity::Day => date,
+        Granularity::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+        Granularity::Month => date.with_day(1).unwrap(),
+    }
+}
+
+// first day of the period following the one containing date
+fn next_period(date: NaiveDate, granularity: Granularity) -> NaiveDate {
+    let start = period_start(date, granularity);
+    match granularity {
+        Granularity::Day => start + Days::new(1),
+        Granularity::Week => start + Days::new(7),
+        Granularity::Month => start + Months::new(1),
+    }
+}
+
+// gridline dates inside the range, coarser than the granularity for long ranges
+fn ticks(start: NaiveDate, end: NaiveDate, granularity: Granularity) -> Vec<NaiveDate> {
+    let days = (end - start).num_days();
+    let granularity = match granularity {
+        Granularity::Day if days > 62 => Granularity::Month,
+        Granularity::Week if days > 366 => Granularity::Month,
+        other => other,
+    };
+
+    let mut ticks = Vec::new();
+    let mut tick = next_period(start, granularity);
+    while tick <= end {
+        ticks.push(tick);
+        tick = next_period(tick, granularity);
+    }
+    ticks
+}
+
+// build timeline bars for projects overlapping the inclusive range [start, end]
+// in progress projects get open ended bars up to today
+pub fn build(
+    projects: Vec<models::Project>,
+    start: NaiveDate,
+    end: NaiveDate,
+    granularity: Granularity,
+    today: NaiveDate,
+) -> Timeline {
+    let total_days = ((end - start).num_days() + 1) as f64;
+
+    let bars = projects
+        .into_iter()
+        .map(|project| {
+            let in_progress = project.status == 1;
+            // a completion (or start in the future) before the start still shows as a single period
+            let finish = if in_progress {
+                today
+            } else {
+                project.completion_date
+            };
+            let finish = finish.max(project.start_date);
+            (project, finish, in_progress)
+        })
+        .filter(|(project, finish, _)| project.start_date <= end && *finish >= start)
+        .map(|(project, finish, in_progress)| {
+            // snap to whole periods, then clip to the visible range
+            let bar_start = period_start(project.start_date, granularity).max(start);
+            let bar_end = (next_period(finish, granularity) - Days::new(1)).min(end);
+            Bar {
+                id: project.id,
+                name: project.name,
+                category: project.category,
+                start: bar_start,
+                end: bar_end,
+                in_progress,
+                offset: (bar_start - start).num_days() as f64 / total_days * 100.0,
+                width: ((bar_end - bar_start).num_days() + 1) as f64 / total_days * 100.0,
+            }
+        })
+        .collect();
+
+    Timeline {
+        start,
+        end,
+        granularity,
+        ticks: ticks(start, end, granularity),
+        bars,
+    }
+}
+
+const SVG_WIDTH: f64 = 1000.0;
+const SVG_LABEL_WIDTH: f64 = 200.0;
+const SVG_HEADER_HEIGHT: f64 = 24.0;
+const SVG_ROW_HEIGHT: f64 = 24.0;
+pub const SVG_COLORS: [&str; 6] = [
+    "#e19f42", "#4299e1", "#48bb78", "#9f7aea", "#ed64a6", "#38b2ac",
+];
+const SVG_DEFAULT_COLOR: &str = "#9ca3af";
+
+#[derive(Debug)]
+pub struct SvgRow {
+    pub label: String,
+    pub title: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub color: &'static str,
+    pub in_progress: bool,
+}
+
+#[derive(Debug)]
+pub struct SvgGridline {
+    pub x: f64,
+    pub label: String,
+}
+
+#[derive(Debug)]
+pub struct SvgChart {
+    pub width: f64,
+    pub height: f64,
+    pub label_width: f64,
+    pub header_height: f64,
+    pub row_height: f64,
+    pub gridlines: Vec<SvgGridline>,
+    pub rows: Vec<SvgRow>,
+    // x position of the today marker, if today is inside the range
+    pub today: Option<f64>,
+}
+
+// lay out a timeline as a gantt chart, colored by position of the category in categories
+pub fn svg_chart(timeline: &Timeline, categories: &[&str], today: NaiveDate) -> SvgChart {
+    let chart_width = SVG_WIDTH - SVG_LABEL_WIDTH;
+    let total_days = ((timeline.end - timeline.start).num_days() + 1) as f64;
+    let x_of = |date: NaiveDate| {
+        SVG_LABEL_WIDTH + (date - timeline.start).num_days() as f64 / total_days * chart_width
+    };
+
+    // month gridlines with labels, starting with the (possibly partial) first month
+    let mut gridlines = vec![SvgGridline {
+        x: SVG_LABEL_WIDTH,
+        label: timeline.start.format("%b %Y").to_string(),
+    }];
+    gridlines.extend(
+        ticks(timeline.start, timeline.end, Granularity::Month)
+            .into_iter()
+            .map(|tick| SvgGridline {
+                x: x_of(tick),
+                label: tick.format("%b %Y").to_string(),
+            }),
+    );
+
+    let rows: Vec<SvgRow> = timeline
+        .bars
+        .iter()
+        .enumerate()
+        .map(|(index, bar)| SvgRow {
+            label: bar.name.clone(),
+            title: if bar.in_progress {
+                format!("{} ({} - in progress)", bar.name, bar.start)
+            } else {
+                format!("{} ({} - {})", bar.name, bar.start, bar.end)
+            },
+            x: SVG_LABEL_WIDTH + bar.offset / 100.0 * chart_width,
+            y: SVG_HEADER_HEIGHT + index as f64 * SVG_ROW_HEIGHT,
+            width: bar.width / 100.0 * chart_width,
+            color: categories
+                .iter()
+                .position(|category| *category == bar.category)
+                .map_or(SVG_DEFAULT_COLOR, |index| {
+                    SVG_COLORS[index % SVG_COLORS.len()]
+                }),
+            in_progress: bar.in_progress,
+        })
+        .collect();
+
+    SvgChart {
+        width: SVG_WIDTH,
+        height: SVG_HEADER_HEIGHT + rows.len() as f64 * SVG_ROW_HEIGHT + 4.0,
+        label_width: SVG_LABEL_WIDTH,
+        header_height: SVG_HEADER_HEIGHT,
+        row_height: SVG_ROW_HEIGHT,
+        gridlines,
+        rows,
+        today: (timeline.start..=timeline.end)
+            .contains(&today)
+            .then(|| x_of(today)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::Project;
+
+    fn day(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    fn completed(id: u64, start: NaiveDate, completion: NaiveDate) -> Project {
+        Project {
+            status: 2,
+            completion_date: completion,
+            ..Project::example(id, "Done", "Work", start)
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn periods_start_on_monday_and_the_first_of_the_month() {
+        // 2026-03-04 is a wednesday
+        assert_eq!(period_start(day(3, 4), Granularity::Day), day(3, 4));
+        assert_eq!(period_start(day(3, 4), Granularity::Week), day(3, 2));
+        assert_eq!(period_start(day(3, 2), Granularity::Week), day(3, 2));
+        assert_eq!(period_start(day(3, 4), Granularity::Month), day(3, 1));
+        assert_eq!(next_period(day(3, 4), Granularity::Day), day(3, 5));
+        assert_eq!(next_period(day(3, 4), Granularity::Week), day(3, 9));
+        assert_eq!(next_period(day(1, 31), Granularity::Month), day(2, 1));
+        assert_eq!(
+            next_period(day(12, 31), Granularity::Month),
+            NaiveDate::from_ymd_opt(2027, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn ticks_follow_the_granularity() {
+        assert_eq!(
+            ticks(day(3, 1), day(3, 5), Granularity::Day),
+            [day(3, 2), day(3, 3), day(3, 4), day(3, 5)]
+        );
+        assert_eq!(
+            ticks(day(3, 1), day(3, 20), Granularity::Week),
+            [day(3, 2), day(3, 9), day(3, 16)]
+        );
+        assert_eq!(
+            ticks(day(1, 1), day(3, 31), Granularity::Month),
+            [day(2, 1), day(3, 1)]
+        );
+        assert!(ticks(day(3, 1), day(3, 1), Granularity::Day).is_empty());
+    }
+
+    #[test]
+    fn long_ranges_get_month_ticks() {
+        assert_eq!(
+            ticks(day(1, 15), day(4, 30), Granularity::Day),
+            [day(2, 1), day(3, 1), day(4, 1)]
+        );
+        assert_eq!(
+            ticks(
+                day(1, 1),
+                NaiveDate::from_ymd_opt(2027, 2, 1).unwrap(),
+                Granularity::Week
+            )
+            .len(),
+            13
+        );
+    }
+
+    #[test]
+    fn bars_are_placed_in_percent_of_the_range() {
+        let timeline = build(
+            vec![completed(1, day(3, 3), day(3, 5))],
+            day(3, 1),
+            day(3, 10),
+            Granularity::Day,
+            day(3, 20),
+        );
+        let bar = &timeline.bars[0];
+        assert_eq!((bar.start, bar.end), (day(3, 3), day(3, 5)));
+        assert!(!bar.in_progress);
+        assert_close(bar.offset, 20.0);
+        assert_close(bar.width, 30.0);
+    }
+
+    #[test]
+    fn bars_are_clipped_to_the_range() {
+        let timeline = build(
+            vec![
+                completed(1, day(2, 20), day(3, 2)),
+                completed(2, day(3, 9), day(4, 2)),
+                completed(3, day(2, 1), day(4, 1)),
+            ],
+            day(3, 1),
+            day(3, 10),
+            Granularity::Day,
+            day(3, 20),
+        );
+        let geometry: Vec<_> = timeline
+            .bars
+            .iter()
+            .map(|bar| (bar.start, bar.end))
+            .collect();
+        assert_eq!(
+            geometry,
+            [
+                (day(3, 1), day(3, 2)),
+                (day(3, 9), day(3, 10)),
+                (day(3, 1), day(3, 10))
+            ]
+        );
+        assert_close(timeline.bars[2].offset, 0.0);
+        assert_close(timeline.bars[2].width, 100.0);
+    }
+
+    #[test]
+    fn projects_outside_the_range_are_left_out() {
+        let timeline = build(
+            vec![
+                completed(1, day(2, 1), day(2, 28)),
+                completed(2, day(3, 11), day(3, 12)),
+                completed(3, day(2, 1), day(3, 1)),
+                completed(4, day(3, 10), day(3, 12)),
+            ],
+            day(3, 1),
+            day(3, 10),
+            Granularity::Day,
+            day(3, 20),
+        );
+        let ids: Vec<_> = timeline.bars.iter().map(|bar| bar.id).collect();
+        assert_eq!(ids, [3, 4]);
+    }
+
+    #[test]
+    fn in_progress_bars_run_until_today() {
+        let project = Project {
+            status: 1,
+            ..Project::example(1, "Open", "Work", day(3, 3))
+        };
+        let timeline = build(
+            vec![project.clone()],
+            day(3, 1),
+            day(3, 10),
+            Granularity::Day,
+            day(3, 6),
+        );
+        let bar = &timeline.bars[0];
+        assert!(bar.in_progress);
+        assert_eq!((bar.start, bar.end), (day(3, 3), day(3, 6)));
+
+        // started after the end of the range
+        let project = Project {
+            start_date: day(3, 12),
+            ..project
+        };
+        let timeline = build(
+            vec![project],
+            day(3, 1),
+            day(3, 10),
+            Granularity::Day,
+            day(3, 20),
+        );
+        assert!(timeline.bars.is_empty());
+    }
+
+    #[test]
+    fn bars_snap_to_whole_periods() {
+        let projects = vec![completed(1, day(3, 4), day(3, 11))];
+        let timeline = build(
+            projects.clone(),
+            day(3, 1),
+            day(3, 31),
+            Granularity::Week,
+            day(3, 20),
+        );
+        assert_eq!(
+            (timeline.bars[0].start, timeline.bars[0].end),
+            (day(3, 2), day(3, 15))
+        );
+
+        let timeline = build(
+            projects,
+            day(1, 1),
+            day(12, 31),
+            Granularity::Month,
+            day(3, 20),
+        );
+        assert_eq!(
+            (timeline.bars[0].start, timeline.bars[0].end),
+            (day(3, 1), day(3, 31))
+        );
+        assert_close(timeline.bars[0].offset, 59.0 / 365.0 * 100.0);
+        assert_close(timeline.bars[0].width, 31.0 / 365.0 * 100.0);
+    }
+
+    #[test]
+    fn bars_never_end_before_they_start() {
+        let future = Project {
+            status: 1,
+            ..Project::example(2, "Future", "Work", day(3, 8))
+        };
+        let timeline = build(
+            vec![completed(1, day(3, 5), day(3, 2)), future],
+            day(3, 1),
+            day(3, 10),
+            Granularity::Day,
+            day(3, 4),
+        );
+        for bar in &timeline.bars {
+            assert_eq!(bar.start, bar.end);
+            assert_close(bar.width, 10.0);
+        }
+        assert_eq!(timeline.bars[0].start, day(3, 5));
+        assert_eq!(timeline.bars[1].start, day(3, 8));
+    }
+
+    #[test]
+    fn svg_rows_scale_to_the_chart() {
+        let open = Project {
+            status: 1,
+            category: "Home".to_string(),
+            ..Project::example(2, "Open", "Work", day(3, 9))
+        };
+        let other = Project {
+            category: "Other".to_string(),
+            ..completed(3, day(3, 1), day(3, 1))
+        };
+        let timeline = build(
+            vec![completed(1, day(3, 3), day(3, 5)), open, other],
+            day(3, 1),
+            day(3, 10),
+            Granularity::Day,
+            day(3, 6),
+        );
+        let chart = svg_chart(&timeline, &["Work", "Home"], day(3, 6));
+
+        let rows: Vec<_> = chart
+            .rows
+            .iter()
+            .map(|row| (row.title.as_str(), row.color))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("Done (2026-03-03 - 2026-03-05)", SVG_COLORS[0]),
+                ("Open (2026-03-09 - in progress)", SVG_COLORS[1]),
+                ("Done (2026-03-01 - 2026-03-01)", SVG_DEFAULT_COLOR),
+            ]
+        );
+        assert_close(chart.rows[0].x, 360.0);
+        assert_close(chart.rows[0].width, 240.0);
+        assert_close(chart.rows[2].y, SVG_HEADER_HEIGHT + 2.0 * SVG_ROW_HEIGHT);
+        assert_close(chart.height, SVG_HEADER_HEIGHT + 3.0 * SVG_ROW_HEIGHT + 4.0);
+        assert_close(chart.today.unwrap(), 600.0);
+    }
+
+    #[test]
+    fn svg_gridlines_mark_months() {
+        let timeline = build(
+            Vec::new(),
+            day(1, 15),
+            day(3, 20),
+            Granularity::Week,
+            day(6, 1),
+        );
+        let chart = svg_chart(&timeline, &[], day(6, 1));
+        let labels: Vec<_> = chart
+            .gridlines
+            .iter()
+            .map(|gridline| gridline.label.as_str())
+            .collect();
+        assert_eq!(labels, ["Jan 2026", "Feb 2026", "Mar 2026"]);
+        assert_close(chart.gridlines[0].x, SVG_LABEL_WIDTH);
+        assert!(chart.rows.is_empty());
+        assert_eq!(chart.today, None);
+    }
+}
@@ -0,0 +1,24 @@
+use std::sync::OnceLock;
+
+// prefix of every url when the app is served below a path, e.g. "/projects", set once on startup
+static BASE_PATH: OnceLock<String> = OnceLock::new();
+
+pub fn set_base_path(base_path: &str) {
+    BASE_PATH
+        .set(base_path.to_string())
+        .expect("base path is set once");
+}
+
+// "" when served at the root of the domain, never ends with a slash
+pub fn base_path() -> &'static str {
+    BASE_PATH.get().map_or("", String::as_str)
+}
+
+// url of a path of the app, templates and redirects link through this so the app can be mounted anywhere
+pub fn url(path: &str) -> String {
+    // the index of a mounted app is /projects, not /projects/
+    if path == "/" && !base_path().is_empty() {
+        return base_path().to_string();
+    }
+    format!("{}{}", base_path(), path)
+}
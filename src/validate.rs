@@ -0,0 +1,229 @@
+use std::net::IpAddr;
+
+use crate::config;
+
+// limits of user input, checked before anything is written to the database
+pub const MAX_NAME_LENGTH: usize = 200;
+pub const MAX_CATEGORY_LENGTH: usize = 64;
+pub const MAX_NOTES_LENGTH: usize = 10_000;
+// largest backup or import file that is accepted
+pub const MAX_UPLOAD_SIZE: usize = 16 * 1024 * 1024;
+
+// trimmed project name, not empty and not longer than the limit
+pub fn project_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        Err("name is empty".to_string())
+    } else if name.chars().count() > MAX_NAME_LENGTH {
+        Err(format!(
+            "name is longer than {} characters",
+            MAX_NAME_LENGTH
+        ))
+    } else {
+        Ok(name)
+    }
+}
+
+// trimmed category name, not empty and not longer than the limit
+pub fn category_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        Err("category name is empty".to_string())
+    } else if name.chars().count() > MAX_CATEGORY_LENGTH {
+        Err(format!(
+            "category name is longer than {} characters",
+            MAX_CATEGORY_LENGTH
+        ))
+    } else {
+        Ok(name)
+    }
+}
+
+pub fn notes(notes: &str) -> Result<&str, String> {
+    if notes.chars().count() > MAX_NOTES_LENGTH {
+        Err(format!(
+            "notes are longer than {} characters",
+            MAX_NOTES_LENGTH
+        ))
+    } else {
+        Ok(notes)
+    }
+}
+
+// webhook target, an http or https url of a public host, so webhooks cannot reach internal services,
+// unless the config allows private hosts or this host
+pub fn webhook_url(url: &str, allowed: &config::WebhookConfig) -> Result<reqwest::Url, String> {
+    let url = reqwest::Url::parse(url.trim()).map_err(|_| "url is not a valid url".to_string())?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err("url has to start with http:// or https://".to_string());
+    }
+    let host = url.host_str().unwrap_or_default();
+    if allowed.allows(host) {
+        return Ok(url);
+    }
+    // ipv6 hosts are written in brackets
+    let public = match host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        Ok(ip) => is_public_ip(ip),
+        // names without a dot resolve through the search domains of the local network
+        Err(_) => {
+            let host = host.trim_end_matches('.');
+            host.contains('.')
+                && !host.ends_with(".localhost")
+                && !host.ends_with(".local")
+                && !host.ends_with(".internal")
+        }
+    };
+    if !public {
+        return Err(
+            "url has to point to a public host, private hosts have to be allowed in the config"
+                .to_string(),
+        );
+    }
+    Ok(url)
+}
+
+// addresses of the internet, not loopback, private, link local or otherwise reserved networks
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                // shared address space of carrier grade nat
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 192 && b == 0 && c == 0)
+                // benchmarking
+                || (a == 198 && (18..20).contains(&b))
+                // reserved
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(ip));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // unique local
+                || (first & 0xfe00) == 0xfc00
+                // link local
+                || (first & 0xffc0) == 0xfe80
+                // documentation
+                || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_trimmed_and_limited() {
+        assert_eq!(project_name("  Paint fence \n"), Ok("Paint fence"));
+        assert_eq!(project_name(" \t "), Err("name is empty".to_string()));
+        // the limit counts characters, not bytes
+        assert!(project_name(&"é".repeat(MAX_NAME_LENGTH)).is_ok());
+        assert_eq!(
+            project_name(&"x".repeat(MAX_NAME_LENGTH + 1)),
+            Err("name is longer than 200 characters".to_string())
+        );
+        assert_eq!(category_name(" Work "), Ok("Work"));
+        assert!(category_name("").is_err());
+        assert!(category_name(&"x".repeat(MAX_CATEGORY_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn notes_may_be_empty_but_not_too_long() {
+        assert_eq!(notes(""), Ok(""));
+        assert_eq!(notes("  kept as is "), Ok("  kept as is "));
+        assert!(notes(&"ü".repeat(MAX_NOTES_LENGTH)).is_ok());
+        assert!(notes(&"x".repeat(MAX_NOTES_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn webhook_urls_need_a_public_http_host() {
+        let public = config::WebhookConfig::default();
+        assert!(webhook_url(" https://hooks.example.com/x ", &public).is_ok());
+        assert!(webhook_url("http://93.184.216.34:8080/hook", &public).is_ok());
+        assert!(webhook_url("http://[2606:4700::1111]/", &public).is_ok());
+        for url in [
+            "not a url",
+            "ftp://example.com/",
+            "file:///etc/passwd",
+            "http://localhost/",
+            "http://intranet/",
+            "http://app.localhost/",
+            "http://printer.local/",
+            "http://metadata.google.internal/",
+            "http://127.0.0.1/",
+            "http://10.0.0.8/",
+            "http://169.254.169.254/latest",
+            "http://[::1]/",
+            "http://[::ffff:192.168.1.1]/",
+            "http://[fd00::1]/",
+        ] {
+            assert!(webhook_url(url, &public).is_err(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn private_hosts_can_be_allowed() {
+        let listed = config::WebhookConfig {
+            allow_private: false,
+            allowed_hosts: vec![
+                "HomeAssistant.local".to_string(),
+                "192.168.1.20".to_string(),
+                "[fd00::2]".to_string(),
+            ],
+        };
+        assert!(webhook_url("http://homeassistant.local:8123/api/webhook/x", &listed).is_ok());
+        assert!(webhook_url("http://homeassistant.local./x", &listed).is_ok());
+        assert!(webhook_url("http://192.168.1.20/hook", &listed).is_ok());
+        assert!(webhook_url("http://[fd00::2]/hook", &listed).is_ok());
+        assert!(webhook_url("http://192.168.1.21/hook", &listed).is_err());
+        assert!(webhook_url("http://nas.local/hook", &listed).is_err());
+        assert!(webhook_url("ftp://192.168.1.20/", &listed).is_err());
+
+        let private = config::WebhookConfig {
+            allow_private: true,
+            allowed_hosts: Vec::new(),
+        };
+        assert!(webhook_url("http://nas/hook", &private).is_ok());
+        assert!(webhook_url("http://10.0.0.8/hook", &private).is_ok());
+        assert!(webhook_url("file:///etc/passwd", &private).is_err());
+    }
+
+    #[test]
+    fn reserved_networks_are_not_public() {
+        for ip in [
+            "0.0.0.0",
+            "100.64.0.1",
+            "192.0.0.1",
+            "198.18.0.1",
+            "240.0.0.1",
+            "255.255.255.255",
+            "224.0.0.1",
+            "192.0.2.1",
+            "fe80::1",
+            "2001:db8::1",
+            "ff02::1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+        assert!(is_public_ip("1.1.1.1".parse().unwrap()));
+        assert!(is_public_ip("100.128.0.1".parse().unwrap()));
+        assert!(is_public_ip("2a00:1450::1".parse().unwrap()));
+    }
+}
@@ -0,0 +1,479 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Local, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use sha2::Sha256;
+use tokio::sync::{mpsc, Semaphore};
+
+use crate::config;
+use crate::db;
+use crate::models;
+use crate::validate;
+
+pub const EVENTS: [&str; 4] = [
+    "project.added",
+    "project.started",
+    "project.completed",
+    "project.deleted",
+];
+
+const MAX_ATTEMPTS: u64 = 5;
+const FIRST_RETRY: Duration = Duration::from_secs(1);
+const TIMEOUT: Duration = Duration::from_secs(10);
+// events waiting for delivery, further events are dropped while the queue is full
+const QUEUE_SIZE: usize = 1000;
+// deliveries in flight, including the ones waiting for a retry
+const MAX_DELIVERIES: usize = 32;
+
+// queue of events waiting to be delivered to webhooks
+#[derive(Clone)]
+pub struct Dispatcher {
+    sender: mpsc::Sender<models::ProjectEvent>,
+}
+
+impl Dispatcher {
+    // never waits, a slow receiver must not hold up the request that caused the event
+    pub fn send(&self, event: models::ProjectEvent) {
+        match self.sender.try_send(event) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(event)) => {
+                println!(
+                    "{} - Webhooks - queue full, dropped {} of project with id {}",
+                    Local::now(),
+                    event.event,
+                    event.project.id
+                );
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                println!("{} - Webhooks - queue closed, dropped event", Local::now());
+            }
+        }
+    }
+}
+
+// start the background worker delivering queued events
+pub fn spawn(db: db::Db, allowed: config::WebhookConfig) -> Dispatcher {
+    let (sender, mut receiver) = mpsc::channel::<models::ProjectEvent>(QUEUE_SIZE);
+    let deliveries = Arc::new(Semaphore::new(MAX_DELIVERIES));
+    let client = reqwest::Client::builder()
+        .timeout(TIMEOUT)
+        .dns_resolver(Arc::new(PublicResolver {
+            allowed: allowed.clone(),
+        }))
+        // a redirect could lead to an internal host, receivers have to answer themselves
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("Could not build webhook client");
+
+    tokio::spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            let webhooks = match db.get_webhooks(event.project.owner_id).await {
+                Ok(webhooks) => webhooks,
+                Err(err) => {
+                    println!(
+                        "{} - Webhooks - could not load webhooks: {}",
+                        Local::now(),
+                        err
+                    );
+                    continue;
+                }
+            };
+            let body = match serde_json::to_vec(&event) {
+                Ok(body) => body,
+                Err(err) => {
+                    println!(
+                        "{} - Webhooks - could not serialize event: {}",
+                        Local::now(),
+                        err
+                    );
+                    continue;
+                }
+            };
+
+            // deliver to each subscribed webhook independently so retries don't block the queue
+            for webhook in webhooks
+                .into_iter()
+                .filter(|webhook| subscribed(webhook, &event.event))
+            {
+                // webhooks stored before urls were checked may point at internal hosts
+                if let Err(err) = validate::webhook_url(&webhook.url, &allowed) {
+                    println!(
+                        "{} - Webhooks - skipped webhook with id {}: {}",
+                        Local::now(),
+                        webhook.id,
+                        err
+                    );
+                    continue;
+                }
+                // the queue fills up while all deliveries are busy
+                let permit = deliveries
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("semaphore is never closed");
+                let delivery = deliver(
+                    db.clone(),
+                    client.clone(),
+                    webhook,
+                    event.event.clone(),
+                    body.clone(),
+                );
+                tokio::spawn(async move {
+                    delivery.await;
+                    drop(permit);
+                });
+            }
+        }
+    });
+
+    Dispatcher { sender }
+}
+
+// resolves webhook hosts and refuses private addresses, so a public name cannot lead to an internal service,
+// hosts allowed in the config may resolve to any address
+struct PublicResolver {
+    allowed: config::WebhookConfig,
+}
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        let allowed = self.allowed.allows(&host);
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| allowed || validate::is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+// empty event list subscribes to all events
+fn subscribed(webhook: &models::Webhook, event: &str) -> bool {
+    webhook.events.is_empty() || webhook.events.split(',').any(|name| name.trim() == event)
+}
+
+// hex encoded HMAC-SHA256 of "<timestamp>.<body>", receivers reject old timestamps to stop replays
+fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}.", timestamp).as_bytes());
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// post event with exponential backoff between attempts, every attempt is logged
+// server errors, rate limits and network errors are retried
+async fn deliver(
+    db: db::Db,
+    client: reqwest::Client,
+    webhook: models::Webhook,
+    event: String,
+    body: Vec<u8>,
+) {
+    let mut delay = FIRST_RETRY;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        // every attempt is signed with its own time, so retries are not taken for replays
+        let timestamp = Utc::now().timestamp();
+        let signature = format!("sha256={}", signature(&webhook.secret, timestamp, &body));
+        let result = client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header("X-Webhook-Event", &event)
+            .header("X-Webhook-Timestamp", timestamp.to_string())
+            .header("X-Webhook-Signature", &signature)
+            .body(body.clone())
+            .send()
+            .await;
+
+        // other client errors would be answered the same way again, only these are worth a retry
+        let (status_code, error, success, retry) = match result {
+            Ok(response) => {
+                let status = response.status();
+                (
+                    Some(status.as_u16() as u64),
+                    String::new(),
+                    status.is_success(),
+                    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+                )
+            }
+            Err(err) => (None, err.to_string(), false, true),
+        };
+
+        if let Err(err) = db
+            .add_webhook_delivery(webhook.id, &event, attempt, status_code, &error)
+            .await
+        {
+            println!(
+                "{} - Webhooks - could not log delivery: {}",
+                Local::now(),
+                err
+            );
+        }
+
+        if success {
+            println!(
+                "{} - Webhooks - delivered {} to webhook with id {}",
+                Local::now(),
+                event,
+                webhook.id
+            );
+            return;
+        }
+
+        if !retry || attempt == MAX_ATTEMPTS {
+            println!(
+                "{} - Webhooks - giving up on {} for webhook with id {} after {} attempts",
+                Local::now(),
+                event,
+                webhook.id,
+                attempt
+            );
+            return;
+        }
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+    use axum::http::{HeaderMap, StatusCode};
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    type Requests = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+    // receiver on a local port answering with the given statuses in turn, then with 200
+    async fn receiver(statuses: Vec<u16>) -> (String, Requests) {
+        let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let app = axum::Router::new().route(
+            "/hook",
+            axum::routing::post(move |headers: HeaderMap, body: Bytes| {
+                let recorded = recorded.clone();
+                let statuses = statuses.clone();
+                async move {
+                    let mut recorded = recorded.lock().unwrap();
+                    let status = statuses.get(recorded.len()).copied().unwrap_or(200);
+                    recorded.push((headers, body));
+                    StatusCode::from_u16(status).unwrap()
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, requests)
+    }
+
+    async fn webhook(db: &db::Db, url: &str) -> (u64, models::Webhook) {
+        let user_id = db.add_user("alice", "hash", &[]).await.unwrap();
+        db.add_webhook(user_id, url, "secret", "").await.unwrap();
+        (user_id, db.get_webhooks(user_id).await.unwrap().remove(0))
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let body = br#"{"event":"project.added"}"#;
+        assert_eq!(
+            signature("secret", 1700000000, body),
+            "853608e33c90d3b88c5e2bed54b27de113588b00ac21457255425d9c947236a9"
+        );
+        assert_ne!(
+            signature("secret", 1700000001, body),
+            signature("secret", 1700000000, body)
+        );
+        assert_ne!(
+            signature("other", 1700000000, body),
+            signature("secret", 1700000000, body)
+        );
+    }
+
+    #[test]
+    fn empty_event_list_subscribes_to_all() {
+        let mut webhook = models::Webhook {
+            id: 1,
+            url: String::new(),
+            secret: String::new(),
+            events: String::new(),
+            creation_date: Utc::now().date_naive(),
+        };
+        assert!(subscribed(&webhook, "project.deleted"));
+        webhook.events = "project.added, project.completed".to_string();
+        assert!(subscribed(&webhook, "project.completed"));
+        assert!(!subscribed(&webhook, "project.deleted"));
+    }
+
+    #[tokio::test]
+    async fn delivery_is_signed_and_logged() {
+        let db = db::test_db().await;
+        let (url, requests) = receiver(vec![]).await;
+        let (user_id, webhook) = webhook(&db, &url).await;
+        let body = br#"{"event":"project.added"}"#.to_vec();
+
+        deliver(
+            db.clone(),
+            reqwest::Client::new(),
+            webhook,
+            "project.added".to_string(),
+            body.clone(),
+        )
+        .await;
+
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 1);
+        let (headers, received) = &requests[0];
+        assert_eq!(received.as_ref(), body.as_slice());
+        assert_eq!(headers["x-webhook-event"], "project.added");
+        let timestamp: i64 = headers["x-webhook-timestamp"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((Utc::now().timestamp() - timestamp).abs() < 60);
+        assert_eq!(
+            headers["x-webhook-signature"].to_str().unwrap(),
+            format!("sha256={}", signature("secret", timestamp, &body))
+        );
+
+        let deliveries = db.get_webhook_deliveries(user_id, 10).await.unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].attempt, 1);
+        assert_eq!(deliveries[0].status_code, Some(200));
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_retried_with_backoff() {
+        let db = db::test_db().await;
+        let (url, requests) = receiver(vec![500, 503]).await;
+        let (user_id, webhook) = webhook(&db, &url).await;
+
+        let started = Instant::now();
+        deliver(
+            db.clone(),
+            reqwest::Client::new(),
+            webhook,
+            "project.started".to_string(),
+            b"{}".to_vec(),
+        )
+        .await;
+
+        // waits one second after the first failure and two after the second
+        assert!(started.elapsed() >= FIRST_RETRY * 3);
+        assert_eq!(requests.lock().unwrap().len(), 3);
+        let mut deliveries = db.get_webhook_deliveries(user_id, 10).await.unwrap();
+        deliveries.sort_by_key(|delivery| delivery.attempt);
+        let attempts: Vec<(u64, Option<u64>)> = deliveries
+            .iter()
+            .map(|delivery| (delivery.attempt, delivery.status_code))
+            .collect();
+        assert_eq!(
+            attempts,
+            vec![(1, Some(500)), (2, Some(503)), (3, Some(200))]
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limited_delivery_is_retried() {
+        let db = db::test_db().await;
+        let (url, requests) = receiver(vec![429]).await;
+        let (_, webhook) = webhook(&db, &url).await;
+
+        deliver(
+            db.clone(),
+            reqwest::Client::new(),
+            webhook,
+            "project.started".to_string(),
+            b"{}".to_vec(),
+        )
+        .await;
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let db = db::test_db().await;
+        let (url, requests) = receiver(vec![404, 500]).await;
+        let (user_id, webhook) = webhook(&db, &url).await;
+
+        deliver(
+            db.clone(),
+            reqwest::Client::new(),
+            webhook,
+            "project.started".to_string(),
+            b"{}".to_vec(),
+        )
+        .await;
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        let deliveries = db.get_webhook_deliveries(user_id, 10).await.unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].status_code, Some(404));
+    }
+
+    #[tokio::test]
+    async fn unreachable_receiver_is_logged_as_error() {
+        let db = db::test_db().await;
+        // bind and drop a listener to get a port nobody listens on
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        drop(listener);
+        let (user_id, webhook) = webhook(&db, &url).await;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(1))
+            .build()
+            .unwrap();
+        let delivery = deliver(
+            db.clone(),
+            client,
+            webhook,
+            "project.deleted".to_string(),
+            b"{}".to_vec(),
+        );
+        // the first attempt is logged before the backoff, the retries are not waited for
+        let _ = tokio::time::timeout(Duration::from_millis(500), delivery).await;
+
+        let deliveries = db.get_webhook_deliveries(user_id, 10).await.unwrap();
+        assert_eq!(deliveries[0].status_code, None);
+        assert!(!deliveries[0].error.is_empty());
+    }
+
+    #[tokio::test]
+    async fn resolver_refuses_loopback_names() {
+        let resolver = PublicResolver {
+            allowed: config::WebhookConfig::default(),
+        };
+        let name: reqwest::dns::Name = "localhost".parse().unwrap();
+        assert!(reqwest::dns::Resolve::resolve(&resolver, name)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn resolver_resolves_allowed_hosts() {
+        let resolver = PublicResolver {
+            allowed: config::WebhookConfig {
+                allow_private: false,
+                allowed_hosts: vec!["localhost".to_string()],
+            },
+        };
+        let name: reqwest::dns::Name = "localhost".parse().unwrap();
+        let addrs: Vec<_> = reqwest::dns::Resolve::resolve(&resolver, name)
+            .await
+            .unwrap()
+            .collect();
+        assert!(addrs.iter().all(|addr| addr.ip().is_loopback()));
+        assert!(!addrs.is_empty());
+    }
+}